@@ -0,0 +1,89 @@
+use crate::Logger;
+use std::{backtrace::Backtrace, io};
+
+/// Functions from the standard library and the runtime, which usually aren't interesting.
+const RUNTIME_PREFIXES: &[&str] = &[
+	"std::",
+	"core::",
+	"alloc::",
+	"__rust",
+	"rust_begin_unwind",
+	"__libc",
+	"_start",
+];
+
+impl<T: io::Write + Send + Sync + 'static> Logger<T> {
+	/// Format a backtrace with one line per frame, like `3: my_crate::main src/main.rs:12`, to be
+	/// used as (part of) a record's message. If [`color`](Self::color) is enabled, frames from the
+	/// standard library and the runtime are dimmed.
+	///
+	/// A backtrace that wasn't captured is written as is, e.g. `disabled backtrace`.
+	pub fn format_backtrace(&self, backtrace: &Backtrace) -> String {
+		let raw = backtrace.to_string();
+		let mut output = String::new();
+		// the current frame's number and function, waiting for its location
+		let mut frame: Option<(&str, &str)> = None;
+
+		let finish_frame = |output: &mut String, (number, function): (&str, &str), at: &str| {
+			if !output.is_empty() {
+				output.push('\n');
+			}
+			// including trait impls, like `<&dyn core::ops::Fn<()> as ..>::call_once`
+			let path = function
+				.trim_start_matches(['<', '&'])
+				.trim_start_matches("dyn ");
+			let dim = self.color
+				&& RUNTIME_PREFIXES
+					.iter()
+					.any(|prefix| path.starts_with(prefix));
+			if dim {
+				output.push_str("\x1b[2m");
+			}
+			for _ in number.len()..2 {
+				output.push(' ');
+			}
+			output.push_str(number);
+			output.push_str(": ");
+			output.push_str(function);
+			if !at.is_empty() {
+				output.push(' ');
+				// leave out the column
+				output.push_str(match at.rsplit_once(':') {
+					Some((file_and_line, column)) if column.bytes().all(|b| b.is_ascii_digit()) => {
+						file_and_line
+					},
+					_ => at,
+				});
+			}
+			if dim {
+				// reset
+				output.push_str("\x1b[m");
+			}
+		};
+
+		for line in raw.lines() {
+			let line = line.trim();
+			if let Some(at) = line.strip_prefix("at ") {
+				if let Some(current) = frame.take() {
+					finish_frame(&mut output, current, at);
+				}
+			} else if let Some((number, function)) = line
+				.split_once(": ")
+				.filter(|(number, _)| number.bytes().all(|b| b.is_ascii_digit()))
+			{
+				if let Some(current) = frame.replace((number, function)) {
+					finish_frame(&mut output, current, "");
+				}
+			}
+		}
+		if let Some(current) = frame {
+			finish_frame(&mut output, current, "");
+		}
+
+		if output.is_empty() {
+			raw
+		} else {
+			output
+		}
+	}
+}
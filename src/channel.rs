@@ -0,0 +1,95 @@
+use crate::{compat::Metadata, util::push_owned_fields, Level, Logger};
+use std::{
+	io,
+	sync::mpsc::{self, Receiver},
+	time::SystemTime,
+};
+
+/// A record sent by a [`channel_sink`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+	#[allow(missing_docs)]
+	pub level: Level,
+
+	/// The module path the record came from, like `myapp::db`.
+	pub target: String,
+
+	#[allow(missing_docs)]
+	pub file: Option<String>,
+
+	#[allow(missing_docs)]
+	pub line: Option<u32>,
+
+	/// When the record was made.
+	pub time: SystemTime,
+
+	#[allow(missing_docs)]
+	pub message: String,
+
+	/// Field names and their formatted values, like `("count", "3")`.
+	///
+	/// For `tracing`, these are only the event's own fields, not its spans'.
+	pub fields: Vec<(String, String)>,
+}
+
+/// Create a [`Logger`] that sends records to the returned [`Receiver`] instead of formatting them,
+/// e.g. for an in-process log viewer.
+///
+/// Records are dropped once the [`Receiver`] is.
+pub fn channel_sink() -> (Logger<io::Sink>, Receiver<LogRecord>) {
+	let (sender, receiver) = mpsc::channel();
+	let mut logger = Logger::null();
+	logger.channel = Some(sender);
+	(logger, receiver)
+}
+
+impl<T: io::Write + Send + Sync + 'static> Logger<T> {
+	/// Write records that were made earlier, e.g. ones received from a [`channel_sink`] before this
+	/// logger's output was chosen.
+	///
+	/// Each record keeps its own level, location, and timestamp.
+	pub fn replay<I: IntoIterator<Item = LogRecord>>(&self, records: I) {
+		for record in records {
+			let meta = Metadata {
+				level: record.level,
+				target: &record.target,
+				module_path: Some(&record.target),
+				file: record.file.as_deref(),
+				line: record.line,
+			};
+			if !self.is_enabled(meta.level, meta.target) {
+				continue;
+			}
+			if self.channel.is_some() {
+				self.send_record(&meta, record.time, record.message, record.fields);
+				continue;
+			}
+			self.write_record_at(&meta, None, record.time, |output| {
+				push_owned_fields(output, &record.message, &record.fields)
+			});
+		}
+	}
+
+	/// Send a record, if this is a [`channel_sink`].
+	pub(super) fn send_record(
+		&self,
+		meta: &Metadata,
+		time: SystemTime,
+		message: String,
+		fields: Vec<(String, String)>,
+	) {
+		if let Some(channel) = &self.channel {
+			// the receiver hanging up just means nobody's listening anymore
+			let _ = channel.send(LogRecord {
+				level: meta.level,
+				target: meta.module_path.unwrap_or(meta.target).to_owned(),
+				file: meta.file.map(str::to_owned),
+				line: meta.line,
+				time,
+				message,
+				fields,
+			});
+		}
+	}
+}
@@ -0,0 +1,355 @@
+use crate::{util::StringLike, Level};
+use std::ops::{Index, IndexMut};
+
+/// A terminal color.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+	#[allow(missing_docs)]
+	Black,
+	#[allow(missing_docs)]
+	Red,
+	#[allow(missing_docs)]
+	Green,
+	#[allow(missing_docs)]
+	Yellow,
+	#[allow(missing_docs)]
+	Blue,
+	#[allow(missing_docs)]
+	Magenta,
+	#[allow(missing_docs)]
+	Cyan,
+	#[allow(missing_docs)]
+	White,
+
+	/// A color from the 256-color palette.
+	Ansi256(u8),
+
+	/// A 24-bit color.
+	Rgb(u8, u8, u8),
+}
+
+impl Color {
+	/// Write the SGR parameters that set this as the foreground color, e.g. the `31` in `\x1b[31m`.
+	///
+	/// If `bright` is set, basic colors use their bright variant.
+	pub(super) fn push_fg<S: StringLike>(self, output: &mut S, bright: bool) {
+		self.push_sgr(output, if bright { "9" } else { "3" }, "38");
+	}
+
+	/// Like [`push_fg`](Self::push_fg), but for the background color.
+	pub(super) fn push_bg<S: StringLike>(self, output: &mut S, bright: bool) {
+		self.push_sgr(output, if bright { "10" } else { "4" }, "48");
+	}
+
+	fn push_sgr<S: StringLike>(self, output: &mut S, basic: &str, extended: &str) {
+		let code = match self {
+			Color::Black => '0',
+			Color::Red => '1',
+			Color::Green => '2',
+			Color::Yellow => '3',
+			Color::Blue => '4',
+			Color::Magenta => '5',
+			Color::Cyan => '6',
+			Color::White => '7',
+			Color::Ansi256(index) => {
+				output.push_str(extended);
+				output.push_str(";5;");
+				output.push_str(itoa::Buffer::new().format(index));
+				return;
+			},
+			Color::Rgb(r, g, b) => {
+				output.push_str(extended);
+				output.push_str(";2;");
+				output.push_str(itoa::Buffer::new().format(r));
+				output.push(';');
+				output.push_str(itoa::Buffer::new().format(g));
+				output.push(';');
+				output.push_str(itoa::Buffer::new().format(b));
+				return;
+			},
+		};
+		output.push_str(basic);
+		output.push(code);
+	}
+
+	/// Roughly whether dark text is easier to read than light text on top of this color (in its
+	/// bright variant, for basic colors).
+	pub(super) fn is_light(self) -> bool {
+		let (r, g, b) = match self {
+			Color::Black | Color::Red | Color::Blue | Color::Magenta => return false,
+			Color::Green | Color::Yellow | Color::Cyan | Color::White => return true,
+			Color::Ansi256(index @ 0..=15) => {
+				return matches!(index, 2 | 3 | 6 | 7 | 10 | 11 | 14 | 15)
+			},
+			_ => self.to_rgb(),
+		};
+		// perceived brightness, out of 255,000
+		u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114 > 150_000
+	}
+
+	/// The closest color that can be shown with `depth`.
+	pub(super) fn downgrade(self, depth: ColorDepth) -> Self {
+		match (self, depth) {
+			(Color::Rgb(..), ColorDepth::Ansi256) => {
+				let (r, g, b) = self.to_rgb();
+				let cube = |n: u8| match n {
+					0..=47 => 0,
+					48..=114 => 1,
+					_ => (n - 35) / 40,
+				};
+				let cube_color = Color::Ansi256(16 + 36 * cube(r) + 6 * cube(g) + cube(b));
+				let gray = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+				let gray_color = Color::Ansi256(232 + (gray.saturating_sub(3) / 10).min(23) as u8);
+				if distance(self.to_rgb(), gray_color.to_rgb())
+					< distance(self.to_rgb(), cube_color.to_rgb())
+				{
+					gray_color
+				} else {
+					cube_color
+				}
+			},
+			(Color::Ansi256(index @ 0..=7), ColorDepth::Basic) => BASIC[usize::from(index)],
+			(Color::Ansi256(index @ 8..=15), ColorDepth::Basic) => BASIC[usize::from(index - 8)],
+			(Color::Ansi256(_) | Color::Rgb(..), ColorDepth::Basic) => {
+				// go by hue, since plain distance turns most soft colors into gray or yellow
+				let (r, g, b) = self.to_rgb();
+				let (r, g, b) = (i32::from(r), i32::from(g), i32::from(b));
+				let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+				let range = max - min;
+				if range < 32 {
+					return if max < 128 {
+						Color::Black
+					} else {
+						Color::White
+					};
+				}
+				let hue = if max == r {
+					(60 * (g - b) / range).rem_euclid(360)
+				} else if max == g {
+					120 + 60 * (b - r) / range
+				} else {
+					240 + 60 * (r - g) / range
+				};
+				[
+					Color::Red,
+					Color::Yellow,
+					Color::Green,
+					Color::Cyan,
+					Color::Blue,
+					Color::Magenta,
+				][((hue + 30) % 360 / 60) as usize]
+			},
+			_ => self,
+		}
+	}
+
+	/// Roughly what this color looks like, using xterm's palette.
+	fn to_rgb(self) -> (u8, u8, u8) {
+		match self {
+			Color::Black => (0, 0, 0),
+			Color::Red => (205, 0, 0),
+			Color::Green => (0, 205, 0),
+			Color::Yellow => (205, 205, 0),
+			Color::Blue => (0, 0, 238),
+			Color::Magenta => (205, 0, 205),
+			Color::Cyan => (0, 205, 205),
+			Color::White => (229, 229, 229),
+			Color::Ansi256(index @ 0..=7) => BASIC[usize::from(index)].to_rgb(),
+			Color::Ansi256(index @ 8..=15) => {
+				let (r, g, b) = BASIC[usize::from(index - 8)].to_rgb();
+				(
+					r.saturating_add(50),
+					g.saturating_add(50),
+					b.saturating_add(50),
+				)
+			},
+			Color::Ansi256(index @ 16..=231) => {
+				let index = index - 16;
+				let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+				(level(index / 36), level(index / 6 % 6), level(index % 6))
+			},
+			Color::Ansi256(index) => {
+				let gray = 8 + (index - 232) * 10;
+				(gray, gray, gray)
+			},
+			Color::Rgb(r, g, b) => (r, g, b),
+		}
+	}
+}
+
+const BASIC: [Color; 8] = [
+	Color::Black,
+	Color::Red,
+	Color::Green,
+	Color::Yellow,
+	Color::Blue,
+	Color::Magenta,
+	Color::Cyan,
+	Color::White,
+];
+
+/// Squared distance between two colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+	[(r1, r2), (g1, g2), (b1, b2)]
+		.into_iter()
+		.map(|(a, b)| u32::from(a.abs_diff(b)).pow(2))
+		.sum()
+}
+
+/// How some text should look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+	#[allow(missing_docs)]
+	pub bold: bool,
+	#[allow(missing_docs)]
+	pub italic: bool,
+	#[allow(missing_docs)]
+	pub underline: bool,
+	/// The foreground color, or [`None`] to leave it alone.
+	pub color: Option<Color>,
+}
+
+impl Style {
+	/// Only bold.
+	pub const fn bold() -> Self {
+		Self {
+			bold: true,
+			italic: false,
+			underline: false,
+			color: None,
+		}
+	}
+
+	/// Write the escape sequence that applies this style. Writes nothing for the default style.
+	#[cfg(feature = "tracing")]
+	pub(super) fn push_sgr<S: StringLike>(self, output: &mut S) {
+		if self == Self::default() {
+			return;
+		}
+		output.push_str("\x1b[");
+		let mut first = true;
+		for (enabled, code) in [(self.bold, "1"), (self.italic, "3"), (self.underline, "4")] {
+			if enabled {
+				if !first {
+					output.push(';');
+				}
+				output.push_str(code);
+				first = false;
+			}
+		}
+		if let Some(color) = self.color {
+			if !first {
+				output.push(';');
+			}
+			color.push_fg(output, false);
+		}
+		output.push('m');
+	}
+}
+
+/// How many colors the terminal supports.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+	/// The 8 basic colors (and their bright variants).
+	Basic,
+
+	/// The 256-color palette.
+	Ansi256,
+
+	/// 24-bit color.
+	TrueColor,
+}
+
+/// The color of each level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+	#[allow(missing_docs)]
+	pub trace: Color,
+	#[allow(missing_docs)]
+	pub debug: Color,
+	#[allow(missing_docs)]
+	pub info: Color,
+	#[allow(missing_docs)]
+	pub warn: Color,
+	#[allow(missing_docs)]
+	pub error: Color,
+}
+
+impl Theme {
+	/// The default theme for a [`ColorDepth`].
+	pub const fn for_depth(depth: ColorDepth) -> Self {
+		match depth {
+			ColorDepth::Basic => Self::basic(),
+			ColorDepth::Ansi256 => Self::ansi256(),
+			ColorDepth::TrueColor => Self::truecolor(),
+		}
+	}
+
+	/// Blue, cyan, green, yellow, and red, using the basic colors.
+	pub const fn basic() -> Self {
+		Self {
+			trace: Color::Blue,
+			debug: Color::Cyan,
+			info: Color::Green,
+			warn: Color::Yellow,
+			error: Color::Red,
+		}
+	}
+
+	/// Softer versions of [`Theme::basic`], from the 256-color palette.
+	pub const fn ansi256() -> Self {
+		Self {
+			trace: Color::Ansi256(75),
+			debug: Color::Ansi256(80),
+			info: Color::Ansi256(114),
+			warn: Color::Ansi256(221),
+			error: Color::Ansi256(204),
+		}
+	}
+
+	/// Softer versions of [`Theme::basic`], using 24-bit color.
+	pub const fn truecolor() -> Self {
+		Self {
+			trace: Color::Rgb(97, 175, 239),
+			debug: Color::Rgb(86, 182, 194),
+			info: Color::Rgb(152, 195, 121),
+			warn: Color::Rgb(229, 192, 123),
+			error: Color::Rgb(224, 108, 117),
+		}
+	}
+}
+
+impl Index<Level> for Theme {
+	type Output = Color;
+
+	fn index(&self, level: Level) -> &Color {
+		match level {
+			Level::Trace => &self.trace,
+			Level::Debug => &self.debug,
+			Level::Info => &self.info,
+			Level::Warn => &self.warn,
+			Level::Error => &self.error,
+		}
+	}
+}
+
+impl IndexMut<Level> for Theme {
+	/// Lets colors be changed by level, like `logger.theme[Level::Info] = Color::Cyan`.
+	fn index_mut(&mut self, level: Level) -> &mut Color {
+		match level {
+			Level::Trace => &mut self.trace,
+			Level::Debug => &mut self.debug,
+			Level::Info => &mut self.info,
+			Level::Warn => &mut self.warn,
+			Level::Error => &mut self.error,
+		}
+	}
+}
+
+impl Default for Theme {
+	fn default() -> Self {
+		Self::basic()
+	}
+}
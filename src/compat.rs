@@ -1,18 +1,102 @@
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(super) enum Level {
+use std::panic::Location;
+
+/// The severity of a record, from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+	#[allow(missing_docs)]
 	Trace,
+	#[allow(missing_docs)]
 	Debug,
+	#[allow(missing_docs)]
 	Info,
+	#[allow(missing_docs)]
 	Warn,
+	#[allow(missing_docs)]
 	Error,
 }
 
+impl Level {
+	pub(super) const ALL: [Level; 5] = [
+		Level::Trace,
+		Level::Debug,
+		Level::Info,
+		Level::Warn,
+		Level::Error,
+	];
+}
+
+/// A minimum [`Level`]. Records at least as severe as it pass, and [`Off`](LevelFilter::Off)
+/// lets nothing through.
+///
+/// Ordered from least to most verbose, like `log::LevelFilter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LevelFilter {
+	#[allow(missing_docs)]
+	Off,
+	#[allow(missing_docs)]
+	Error,
+	#[allow(missing_docs)]
+	Warn,
+	#[allow(missing_docs)]
+	Info,
+	#[allow(missing_docs)]
+	Debug,
+	#[allow(missing_docs)]
+	Trace,
+}
+
+impl LevelFilter {
+	/// Indexed by `LevelFilter as usize`.
+	pub(super) const ALL: [LevelFilter; 6] = [
+		LevelFilter::Off,
+		LevelFilter::Error,
+		LevelFilter::Warn,
+		LevelFilter::Info,
+		LevelFilter::Debug,
+		LevelFilter::Trace,
+	];
+
+	/// Whether a record at `level` passes this filter.
+	pub fn allows(self, level: Level) -> bool {
+		LevelFilter::from(level) <= self
+	}
+}
+
+impl From<Level> for LevelFilter {
+	fn from(level: Level) -> Self {
+		match level {
+			Level::Trace => LevelFilter::Trace,
+			Level::Debug => LevelFilter::Debug,
+			Level::Info => LevelFilter::Info,
+			Level::Warn => LevelFilter::Warn,
+			Level::Error => LevelFilter::Error,
+		}
+	}
+}
+
 pub(super) struct Metadata<'a> {
 	pub level: Level,
-	pub module_path: &'a str,
+	/// What filters go by. This is usually the module path, but doesn't have to be.
+	pub target: &'a str,
+	/// [`None`] if the record doesn't have one.
+	pub module_path: Option<&'a str>,
+	pub file: Option<&'a str>,
 	pub line: Option<u32>,
 }
 
+impl<'a> Metadata<'a> {
+	/// For records written straight to the [`Logger`](crate::Logger), from the caller's location.
+	pub fn caller(level: Level, target: &'a str, location: &'static Location<'static>) -> Self {
+		Self {
+			level,
+			target,
+			module_path: Some(target),
+			file: Some(location.file()),
+			line: Some(location.line()),
+		}
+	}
+}
+
 #[cfg(feature = "log")]
 impl From<log::Level> for Level {
 	fn from(level: log::Level) -> Self {
@@ -26,12 +110,28 @@ impl From<log::Level> for Level {
 	}
 }
 
+#[cfg(feature = "log")]
+impl From<log::LevelFilter> for LevelFilter {
+	fn from(filter: log::LevelFilter) -> Self {
+		match filter {
+			log::LevelFilter::Off => LevelFilter::Off,
+			log::LevelFilter::Error => LevelFilter::Error,
+			log::LevelFilter::Warn => LevelFilter::Warn,
+			log::LevelFilter::Info => LevelFilter::Info,
+			log::LevelFilter::Debug => LevelFilter::Debug,
+			log::LevelFilter::Trace => LevelFilter::Trace,
+		}
+	}
+}
+
 #[cfg(feature = "log")]
 impl<'a> From<&log::Record<'a>> for Metadata<'a> {
 	fn from(record: &log::Record<'a>) -> Self {
 		Self {
 			level: record.level().into(),
-			module_path: record.module_path().unwrap_or_else(|| record.target()),
+			target: record.target(),
+			module_path: record.module_path().filter(|path| !path.is_empty()),
+			file: record.file(),
 			line: record.line(),
 		}
 	}
@@ -50,12 +150,24 @@ impl From<tracing::Level> for Level {
 	}
 }
 
+#[cfg(feature = "tracing")]
+impl From<tracing::level_filters::LevelFilter> for LevelFilter {
+	fn from(filter: tracing::level_filters::LevelFilter) -> Self {
+		match filter.into_level() {
+			None => LevelFilter::Off,
+			Some(level) => Level::from(level).into(),
+		}
+	}
+}
+
 #[cfg(feature = "tracing")]
 impl<'a> From<&tracing::Metadata<'a>> for Metadata<'a> {
 	fn from(metadata: &tracing::Metadata<'a>) -> Self {
 		Self {
 			level: (*metadata.level()).into(),
-			module_path: metadata.module_path().unwrap_or_else(|| metadata.target()),
+			target: metadata.target(),
+			module_path: metadata.module_path().filter(|path| !path.is_empty()),
+			file: metadata.file(),
 			line: metadata.line(),
 		}
 	}
@@ -0,0 +1,78 @@
+use crate::{
+	compat::Metadata,
+	util::{format_lossy, push_owned_fields, write_args},
+	Level, Logger,
+};
+use std::{fmt, io, panic::Location, time::SystemTime};
+
+/// Builds a record with fields, without going through `log` or `tracing`. See [`Logger::event`].
+#[must_use = "the event isn't written until `emit` is called"]
+pub struct EventBuilder<'a, T: io::Write + Send + Sync + 'static> {
+	logger: &'a Logger<T>,
+	level: Level,
+	target: &'a str,
+	message: String,
+	fields: Vec<(String, String)>,
+}
+
+impl<T: io::Write + Send + Sync + 'static> EventBuilder<'_, T> {
+	/// Add a field. Its value is formatted with [`Debug`](fmt::Debug), like `tracing` fields.
+	pub fn field(mut self, name: &str, value: impl fmt::Debug) -> Self {
+		self.fields
+			.push((name.to_owned(), format_lossy(format_args!("{value:?}"))));
+		self
+	}
+
+	/// Set the message.
+	pub fn message(mut self, message: impl fmt::Display) -> Self {
+		self.message.clear();
+		write_args(&mut self.message, format_args!("{message}"));
+		self
+	}
+
+	/// Write the event. Its location is the caller's file and line.
+	#[track_caller]
+	pub fn emit(self) {
+		let location = Location::caller();
+		let meta = Metadata::caller(self.level, self.target, location);
+		if !self.logger.is_enabled(meta.level, meta.target) {
+			return;
+		}
+		if self.logger.channel.is_some() {
+			self.logger
+				.send_record(&meta, SystemTime::now(), self.message, self.fields);
+			return;
+		}
+		self.logger
+			.write_record_at(&meta, None, SystemTime::now(), |output| {
+				push_owned_fields(output, &self.message, &self.fields)
+			});
+	}
+}
+
+impl<T: io::Write + Send + Sync + fmt::Debug + 'static> fmt::Debug for EventBuilder<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("EventBuilder")
+			.field("level", &self.level)
+			.field("target", &self.target)
+			.field("message", &self.message)
+			.field("fields", &self.fields)
+			.finish_non_exhaustive()
+	}
+}
+
+impl<T: io::Write + Send + Sync + 'static> Logger<T> {
+	/// Start building a record with fields, like
+	/// `logger.event(Level::Info, module_path!()).field("user", 42).message("done").emit()`.
+	///
+	/// `target` is what filters go by, usually `module_path!()`.
+	pub fn event<'a>(&'a self, level: Level, target: &'a str) -> EventBuilder<'a, T> {
+		EventBuilder {
+			logger: self,
+			level,
+			target,
+			message: String::new(),
+			fields: Vec::new(),
+		}
+	}
+}
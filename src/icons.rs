@@ -0,0 +1,104 @@
+use crate::Level;
+use std::ops::{Index, IndexMut};
+
+/// The icons shown before each level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IconSet {
+	#[allow(missing_docs)]
+	pub trace: &'static str,
+	#[allow(missing_docs)]
+	pub debug: &'static str,
+	#[allow(missing_docs)]
+	pub info: &'static str,
+	#[allow(missing_docs)]
+	pub warn: &'static str,
+	#[allow(missing_docs)]
+	pub error: &'static str,
+}
+
+impl IconSet {
+	/// `→ ○ ● ⚠ ✘`
+	pub const fn unicode() -> Self {
+		Self {
+			trace: "→",
+			debug: "○",
+			info: "●",
+			warn: "⚠",
+			error: "✘",
+		}
+	}
+
+	/// `> - * ! x`, which only use ASCII.
+	pub const fn ascii() -> Self {
+		Self {
+			trace: ">",
+			debug: "-",
+			info: "*",
+			warn: "!",
+			error: "x",
+		}
+	}
+
+	/// `[T] [D] [I] [W] [E]`, which are easy to tell apart without color.
+	pub const fn bracketed() -> Self {
+		Self {
+			trace: "[T]",
+			debug: "[D]",
+			info: "[I]",
+			warn: "[W]",
+			error: "[E]",
+		}
+	}
+
+	/// Font Awesome glyphs from [Nerd Fonts](https://www.nerdfonts.com): `angle-double-right`,
+	/// `bug`, `info-circle`, `warning`, and `times-circle`.
+	///
+	/// These only show up if the terminal uses a Nerd Font.
+	pub const fn nerd_font() -> Self {
+		Self {
+			trace: "\u{f101}",
+			debug: "\u{f188}",
+			info: "\u{f05a}",
+			warn: "\u{f071}",
+			error: "\u{f057}",
+		}
+	}
+}
+
+impl Index<Level> for IconSet {
+	type Output = &'static str;
+
+	fn index(&self, level: Level) -> &&'static str {
+		match level {
+			Level::Trace => &self.trace,
+			Level::Debug => &self.debug,
+			Level::Info => &self.info,
+			Level::Warn => &self.warn,
+			Level::Error => &self.error,
+		}
+	}
+}
+
+impl IndexMut<Level> for IconSet {
+	/// Lets icons be changed by level, like `logger.icons[Level::Warn] = "!"`.
+	fn index_mut(&mut self, level: Level) -> &mut &'static str {
+		match level {
+			Level::Trace => &mut self.trace,
+			Level::Debug => &mut self.debug,
+			Level::Info => &mut self.info,
+			Level::Warn => &mut self.warn,
+			Level::Error => &mut self.error,
+		}
+	}
+}
+
+impl Default for IconSet {
+	/// [`IconSet::ascii`] if the `icons-ascii` feature is enabled, otherwise [`IconSet::unicode`].
+	fn default() -> Self {
+		if cfg!(feature = "icons-ascii") {
+			Self::ascii()
+		} else {
+			Self::unicode()
+		}
+	}
+}
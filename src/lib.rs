@@ -4,19 +4,39 @@
 //! #### Features
 //! - `detect-color` - Automatically detect terminal color support.
 //! - `detect-timezone` - If `timestamps` are enabled, the local timezone will automatically be detected and used.
+//! - `icons-ascii` - Use [`IconSet::ascii`] by default, for terminals that can't show the others.
+//!   This takes priority over `icons-unicode`.
+//! - `icons-unicode` - Use [`IconSet::unicode`] by default.
+//! - `kv` - Show the key-value pairs of [`log`] records. This also enables `log`.
 //! - `log` - Enable [`log`] support.
+//! - `network` - Enable [`NetworkWriter`], for sending logs to a TCP or Unix socket.
 //! - `parking_lot` - Use [`parking_lot`] internally.
+//! - `release-max-level-info` - In release builds, drop `trace` and `debug` records at compile time.
+//!   This also enables the same feature of [`log`] and [`tracing`].
 //! - `timestamps` - Enable timestamps.
 //! - `tracing` - Enable [`tracing`] support.
 //!
 //! #### Log level
-//! Set the level using `log` or `tracing` directly.
+//! Set the level with [`Logger::set_level`] (which works while logging), or using `log` or `tracing` directly.
 //!
 //! #### Filtering
-//! To add filtering with [`log`], create a new struct that implements `Log::enabled`, and forward
+//! Levels can be set per target with [`Logger::target_levels`], like `my_crate::db=debug`.
+//! To make one target more verbose for a while, use [`Logger::boost`].
+//!
+//! For other filtering with [`log`], create a new struct that implements `Log::enabled`, and forward
 //! the other methods to `tinylog::Logger`.
 //!
 //! For [`tracing`], [`tracing_subscriber`] already lets you add filters to existing layers.
+//!
+//! #### Multiple outputs
+//! Each [`Logger`] has its own output and settings, so to send the same records to several places
+//! (e.g. colored text to stdout, and plain text to a file), use one [`Logger`] per output.
+//!
+//! For [`tracing`], add each one as its own layer. For [`log`], create a new struct that implements
+//! [`Log`](log::Log) and forwards every method to each `tinylog::Logger`.
+//!
+//! To send records to different places by level (e.g. warnings and errors to stderr), use
+//! [`SplitLogger`].
 
 #![forbid(unsafe_code)]
 #![allow(clippy::tabs_in_doc_comments)]
@@ -25,46 +45,723 @@
 #[cfg(all(not(feature = "log"), not(feature = "tracing")))]
 compile_error!("at least one of 'log' or 'tracing' features must be enabled");
 
+mod backtrace;
+mod channel;
+mod color;
 mod compat;
+mod event;
+mod icons;
 #[cfg(feature = "log")]
 mod log_impl;
+#[cfg(feature = "network")]
+mod network;
+mod pager;
+mod split;
+#[cfg(test)]
+mod tests;
+#[cfg(feature = "timestamps")]
+mod timezone;
 #[cfg(feature = "tracing")]
 mod tracing_impl;
 mod util;
 
+pub use crate::{
+	channel::{channel_sink, LogRecord},
+	color::{Color, ColorDepth, Style, Theme},
+	compat::{Level, LevelFilter},
+	event::EventBuilder,
+	icons::IconSet,
+	split::SplitLogger,
+};
+
+#[cfg(feature = "network")]
+pub use crate::network::NetworkWriter;
+#[cfg(feature = "timestamps")]
+pub use crate::timezone::ParseTimezoneError;
+
 use crate::{
-	compat::{Level, Metadata},
-	util::StringLike,
+	compat::Metadata,
+	util::{
+		display_width, push_duration, push_message, strip_ansi, terminal_height, terminal_width,
+		with_local_buf, write_args, Indented, StringLike,
+	},
+};
+use std::{
+	env, fmt,
+	io::{self, IsTerminal},
+	panic::Location,
+	sync::{
+		atomic::{AtomicU64, AtomicU8, Ordering},
+		mpsc, Arc,
+	},
+	time::{Duration, Instant, SystemTime},
 };
-use std::io;
 
+#[cfg(feature = "timestamps")]
+use crate::util::Blank;
 #[cfg(feature = "parking_lot")]
-use parking_lot::Mutex;
-#[cfg(not(feature = "parking_lot"))]
-use std::sync::Mutex;
+use parking_lot::{Mutex, MutexGuard};
+#[cfg(feature = "tracing")]
+use std::sync::atomic::AtomicUsize;
 #[cfg(feature = "timestamps")]
-use std::time::SystemTime;
+use std::sync::{
+	atomic::{AtomicI32, AtomicI64},
+	OnceLock,
+};
+#[cfg(not(feature = "parking_lot"))]
+use std::sync::{Mutex, MutexGuard};
+
+/// The most verbose level that can ever be logged, see the `release-max-level-info` feature.
+#[cfg(all(feature = "release-max-level-info", not(debug_assertions)))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Info;
+#[cfg(not(all(feature = "release-max-level-info", not(debug_assertions))))]
+const STATIC_MAX_LEVEL: LevelFilter = LevelFilter::Trace;
 
 /// A tiny logger.
 #[non_exhaustive]
-#[derive(Debug)]
 pub struct Logger<T: io::Write + Send + Sync + 'static = io::Stdout> {
-	output: Mutex<T>,
+	output: Mutex<Output<T>>,
+	/// Skip everything, see [`Logger::null`].
+	discard: bool,
+	/// Send records here instead of writing them, see [`channel_sink`].
+	channel: Option<mpsc::Sender<LogRecord>>,
+	created: Instant,
+	/// Nanoseconds between `created` and the last record, or [`u64::MAX`] if there wasn't one.
+	last_record: AtomicU64,
+	/// Like `last_record`, but for the last notification, see `notify_on`.
+	last_notification: AtomicU64,
+	/// How many records were written at each level, indexed by `Level as usize`.
+	counts: [AtomicU64; 5],
+	/// See [`Logger::throttle`], indexed by `Level as usize`.
+	throttles: [Throttle; 5],
+	/// See [`Logger::route`], indexed by `Level as usize`.
+	#[allow(clippy::type_complexity)]
+	routes: [Option<Mutex<Box<dyn io::Write + Send>>>; 5],
+	/// Tells this logger's span data apart from other loggers' in the same subscriber.
+	#[cfg(feature = "tracing")]
+	id: usize,
+	/// Unix timestamp of the last record, for `collapse_timestamps`.
+	#[cfg(feature = "timestamps")]
+	last_timestamp: AtomicI64,
+	/// Julian day of the last record's date, for `collapse_dates`.
+	#[cfg(feature = "timestamps")]
+	last_date: AtomicI32,
+	/// When the first record with a timestamp was written, for [`TimestampFormat::Elapsed`].
+	#[cfg(feature = "timestamps")]
+	first_timestamp: OnceLock<SystemTime>,
+	/// See [`Logger::set_level`], as `LevelFilter as u8`.
+	level: AtomicU8,
+	/// See [`Logger::boost`].
+	boosts: Mutex<Vec<(String, LevelFilter)>>,
+	/// The most verbose level in `boosts`, as `LevelFilter as u8`, so records no boost could allow
+	/// are rejected without locking it.
+	max_boost: AtomicU8,
+
+	/// Levels for specific targets (module paths), like `("my_crate::db", LevelFilter::Debug)`. The
+	/// longest one that matches a record's target is used instead of [`level`](Self::level), and
+	/// `my_crate::db` matches `my_crate::db` and `my_crate::db::pool`, but not `my_crate::dbx`.
+	///
+	/// Defaults to none. See also [`add_target_level`](Self::add_target_level).
+	pub target_levels: Vec<(String, LevelFilter)>,
 
 	/// Whether color should be enabled.
 	///
 	/// Defaults to [`false`](bool) if `detect-color` is ***not*** enabled.
 	///
 	/// Note: `detect-color` only checks [`io::Stdout`] for color support.
-	/// If you set the output to something else, you should disable `detect-color`.
+	/// If you set the output to something else, you should disable `detect-color`, or set this to
+	/// [`Logger::color_from_env`].
 	pub color: bool,
 
+	/// How many colors the output supports. This decides the default [`theme`](Self::theme), and
+	/// colors it doesn't support are replaced with the closest one it does.
+	///
+	/// Defaults to [`ColorDepth::Basic`] if `detect-color` is ***not*** enabled.
+	pub color_depth: ColorDepth,
+
+	/// The color of each level.
+	///
+	/// Defaults to [`Theme::for_depth`] of the [`color_depth`](Self::color_depth).
+	pub theme: Theme,
+
 	/// The timezone to display timestamps in.
 	///
-	/// If `detect-timezone` is enabled, this defaults to the local timezone.
-	/// Otherwise, this defaults to UTC.
+	/// If `detect-timezone` is enabled, this defaults to the local timezone (or UTC, if it couldn't be
+	/// detected). Otherwise, this defaults to UTC.
 	#[cfg(feature = "timestamps")]
 	pub timezone: time::UtcOffset,
+
+	/// Only show timestamps on records at or above this level, e.g. to keep `trace` records short.
+	///
+	/// Defaults to [`LevelFilter::Trace`].
+	#[cfg(feature = "timestamps")]
+	pub timestamp_from: LevelFilter,
+
+	/// How timestamps are written.
+	///
+	/// Defaults to [`TimestampFormat::Pretty`].
+	#[cfg(feature = "timestamps")]
+	pub timestamp_format: TimestampFormat,
+
+	/// Whether timestamps use a 24-hour clock, like `14:05:09` instead of `2:05:09-PM`.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "timestamps")]
+	pub time_24h: bool,
+
+	/// How much of the date timestamps show.
+	///
+	/// Defaults to [`DateDisplay::Full`].
+	#[cfg(feature = "timestamps")]
+	pub date_display: DateDisplay,
+
+	/// What goes between the time and the date in timestamps.
+	///
+	/// Defaults to `-`, like `5:24:32-AM-2026/01/02`.
+	#[cfg(feature = "timestamps")]
+	pub date_time_separator: char,
+
+	/// What goes between the parts of the date in timestamps.
+	///
+	/// Defaults to `/`, like `2026/01/02`.
+	#[cfg(feature = "timestamps")]
+	pub date_separator: char,
+
+	/// Whether to show the timezone after timestamps, like `+05:30`, or `UTC`.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "timestamps")]
+	pub show_timezone: bool,
+
+	/// Whether to blank out a record's timestamp if it's the same as the previous record's, so only
+	/// the first record of each second shows it.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "timestamps")]
+	pub collapse_timestamps: bool,
+
+	/// Whether to leave the date out of a record's timestamp if it's the same as the previous
+	/// record's, so only the first record of each day shows it.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "timestamps")]
+	pub collapse_dates: bool,
+
+	/// Whether to print a dim separator line before a record that's more severe than the one before
+	/// it (e.g. the first error after a run of infos).
+	///
+	/// Defaults to [`false`](bool).
+	pub highlight_escalation: bool,
+
+	/// The icons shown before each level.
+	///
+	/// Defaults to [`IconSet::default`].
+	pub icons: IconSet,
+
+	/// The name shown for each level, indexed by `Level as usize`, e.g. `["TRC", "DBG", "INF", "WRN",
+	/// "ERR"]`.
+	///
+	/// Defaults to `["trace", "debug", "info", "warn", "error"]`.
+	pub labels: [&'static str; 5],
+
+	/// Whether to show the [`icons`](Self::icons).
+	///
+	/// Defaults to [`true`](bool).
+	pub show_icon: bool,
+
+	/// How the level is shown. Only applies if [`color`](Self::color) is enabled.
+	///
+	/// Defaults to [`LevelStyle::Underline`].
+	pub level_style: LevelStyle,
+
+	/// Whether to show how much time passed since the previous record, like `+12.3ms`.
+	///
+	/// Defaults to [`false`](bool).
+	pub show_delta: bool,
+
+	/// Flush the output after writing a record at or above this level, e.g. so warnings and errors
+	/// aren't stuck in a [`BufWriter`](io::BufWriter) if the program crashes. Lines without a level,
+	/// like [`write_line`](Self::write_line), are flushed unless this is [`LevelFilter::Off`].
+	///
+	/// Defaults to [`LevelFilter::Off`].
+	pub flush_on: LevelFilter,
+
+	/// Keep each record on a single line, for tools that expect one record per line.
+	///
+	/// The message and fields are separated by spaces instead of newlines, and newlines, tabs, and
+	/// carriage returns inside of them are escaped (e.g. as `\n`). Defaults to [`false`](bool).
+	pub single_line: bool,
+
+	/// If set, content that fits on one line is put on the same line as the prefix, after this
+	/// separator (like `" │ "`), instead of on its own indented line.
+	///
+	/// Defaults to [`None`].
+	pub prefix_content_separator: Option<&'static str>,
+
+	/// Show records that are taller than the terminal in a pager (`$PAGER`, or `less -R`) instead,
+	/// if stdout is a terminal. If the pager can't be started, the record is written normally.
+	///
+	/// The pager always shows on the real terminal, so only enable this if the output is stdout.
+	/// The output isn't locked while the pager is open, so other threads can keep logging, but
+	/// [`suspend`](Self::suspend) is still called around it. Defaults to [`false`](bool).
+	pub page_long_content: bool,
+
+	/// How many lines a record needs to be paged, see
+	/// [`page_long_content`](Self::page_long_content).
+	///
+	/// Defaults to [`None`], which uses the terminal's height (`$LINES`, or 24).
+	pub page_threshold: Option<usize>,
+
+	/// Send a desktop notification (with the OSC 9 escape sequence) for records at or above this
+	/// level, showing the prefix and the first line of the record. Only applies if
+	/// [`color`](Self::color) is enabled and [`frame`](Self::frame) is [`Framing::Newline`], and the
+	/// terminal has to support it.
+	///
+	/// At most one notification is sent per second. Defaults to [`LevelFilter::Off`].
+	pub notify_on: LevelFilter,
+
+	/// How records are separated from each other.
+	///
+	/// Defaults to [`Framing::Newline`].
+	pub frame: Framing,
+
+	/// How much of the module path to show.
+	///
+	/// Defaults to [`PathDepth::Full`].
+	pub path_depth: PathDepth,
+
+	/// Shown instead of the module path for records that don't have one, e.g. events bridged from
+	/// somewhere else.
+	///
+	/// Defaults to [`None`], which shows the record's target.
+	pub module_fallback: Option<&'static str>,
+
+	/// How the record's location is shown.
+	pub line_format: LineFormat,
+
+	/// Picks a color for a record's module path. Only applies if [`color`](Self::color) is enabled.
+	///
+	/// Returning [`None`] keeps the default color. Defaults to [`None`].
+	#[allow(clippy::type_complexity)]
+	pub target_color: Option<Arc<dyn Fn(&str) -> Option<Color> + Send + Sync>>,
+
+	/// Called around every write, with a function that does the writing. Useful for hiding a progress
+	/// bar while a record is written, e.g. with `indicatif`'s `ProgressBar::suspend`.
+	///
+	/// This runs while the output is locked, so logging from inside of it will deadlock. Defaults to
+	/// [`None`]. See also [`set_suspend`](Self::set_suspend).
+	#[allow(clippy::type_complexity)]
+	pub suspend: Option<Arc<dyn Fn(&mut dyn FnMut()) + Send + Sync>>,
+
+	/// Changes each formatted record right before it's written. The record's terminator (see
+	/// [`frame`](Self::frame)) is added afterwards.
+	///
+	/// This runs before the output is locked, so it's fine to log from inside of it. It doesn't apply
+	/// to [`write_line`](Self::write_line). Defaults to [`None`].
+	#[allow(clippy::type_complexity)]
+	pub transform: Option<Arc<dyn Fn(&mut String) + Send + Sync>>,
+
+	/// Called with errors from writing or flushing the output, e.g. a closed pipe.
+	///
+	/// This runs while the output is locked, so logging from inside of it will deadlock. Defaults to
+	/// [`None`], which ignores them.
+	#[allow(clippy::type_complexity)]
+	pub on_error: Option<Arc<dyn Fn(&io::Error) + Send + Sync>>,
+
+	/// Fields added to every record, after its own fields, e.g. `component: "auth"`.
+	///
+	/// Defaults to none. See also [`with_fields`](Self::with_fields).
+	pub static_fields: Vec<(String, String)>,
+
+	/// Where a `log` record's key-value pairs are shown.
+	///
+	/// Defaults to [`FieldPosition::AfterMessage`].
+	#[cfg(feature = "kv")]
+	pub kv_position: FieldPosition,
+
+	/// The maximum length of a single field's value, in bytes. Longer values are cut off and marked
+	/// with `…(truncated)`.
+	///
+	/// This also stops formatting the value early, so a huge value doesn't have to fit in memory.
+	/// Defaults to [`None`].
+	#[cfg(feature = "tracing")]
+	pub max_field_len: Option<usize>,
+
+	/// The most fields to show for an event, not counting its message. The rest are left out and
+	/// counted, like `… (+7 more)`.
+	///
+	/// Span fields aren't affected. Defaults to [`None`].
+	#[cfg(feature = "tracing")]
+	pub max_fields: Option<usize>,
+
+	/// How an event's fields are laid out.
+	///
+	/// Defaults to [`FieldStyle::Multiline`].
+	#[cfg(feature = "tracing")]
+	pub field_style: FieldStyle,
+
+	/// Whether to add the id of the span an event happened in as a `span_id` field.
+	///
+	/// Events outside of any span don't get the field. Defaults to [`false`](bool).
+	#[cfg(feature = "tracing")]
+	pub include_span_id: bool,
+
+	/// Whether to hide a span's field if the event (or a more specific span) has a field with the
+	/// same name, so only the most specific value is shown.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "tracing")]
+	pub dedup_fields: bool,
+
+	/// Whether to log how long each span took when it closes, like `load closed: cpu=1.2ms wall=3.4ms`.
+	///
+	/// The CPU time is only measured on Linux, and is left out elsewhere. It only counts time spent
+	/// inside of the span on the thread that entered it. Measuring it costs a syscall every time a
+	/// span is entered or exited, which adds up for spans that are entered often.
+	///
+	/// Defaults to [`false`](bool).
+	#[cfg(feature = "tracing")]
+	pub span_timings: bool,
+
+	/// How span names are shown. Only applies if [`color`](Self::color) is enabled.
+	///
+	/// Defaults to [`Style::bold`].
+	#[cfg(feature = "tracing")]
+	pub span_name_style: Style,
+
+	/// Whether an event should be dimmed. Only applies if [`color`](Self::color) is enabled.
+	///
+	/// For example, `Some(Arc::new(|meta| *meta.level() >= tracing::Level::DEBUG))` dims `debug`
+	/// and `trace` events. Defaults to [`None`].
+	#[cfg(feature = "tracing")]
+	#[allow(clippy::type_complexity)]
+	pub dim: Option<Arc<dyn Fn(&tracing::Metadata) -> bool + Send + Sync>>,
+}
+
+impl<T: io::Write + Send + Sync + fmt::Debug + 'static> fmt::Debug for Logger<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut debug = f.debug_struct("Logger");
+		debug.field("output", &self.output);
+		debug.field("level", &self.level());
+		debug.field("target_levels", &self.target_levels);
+		debug.field("boosts", &*lock(&self.boosts));
+		debug.field("color", &self.color);
+		debug.field("color_depth", &self.color_depth);
+		debug.field("theme", &self.theme);
+		#[cfg(feature = "timestamps")]
+		debug.field("timezone", &self.timezone);
+		#[cfg(feature = "timestamps")]
+		debug.field("timestamp_from", &self.timestamp_from);
+		#[cfg(feature = "timestamps")]
+		debug.field("timestamp_format", &self.timestamp_format);
+		#[cfg(feature = "timestamps")]
+		debug.field("time_24h", &self.time_24h);
+		#[cfg(feature = "timestamps")]
+		debug.field("date_display", &self.date_display);
+		#[cfg(feature = "timestamps")]
+		debug.field("date_time_separator", &self.date_time_separator);
+		#[cfg(feature = "timestamps")]
+		debug.field("date_separator", &self.date_separator);
+		#[cfg(feature = "timestamps")]
+		debug.field("show_timezone", &self.show_timezone);
+		#[cfg(feature = "timestamps")]
+		debug.field("collapse_timestamps", &self.collapse_timestamps);
+		#[cfg(feature = "timestamps")]
+		debug.field("collapse_dates", &self.collapse_dates);
+		debug.field("highlight_escalation", &self.highlight_escalation);
+		debug.field("labels", &self.labels);
+		debug.field("show_icon", &self.show_icon);
+		debug.field("level_style", &self.level_style);
+		debug.field("show_delta", &self.show_delta);
+		debug.field("icons", &self.icons);
+		debug.field("flush_on", &self.flush_on);
+		debug.field("single_line", &self.single_line);
+		debug.field("prefix_content_separator", &self.prefix_content_separator);
+		debug.field("page_long_content", &self.page_long_content);
+		debug.field("page_threshold", &self.page_threshold);
+		debug.field("notify_on", &self.notify_on);
+		debug.field("frame", &self.frame);
+		debug.field("path_depth", &self.path_depth);
+		debug.field("module_fallback", &self.module_fallback);
+		debug.field("line_format", &self.line_format);
+		debug.field("target_color", &self.target_color.as_ref().map(|_| ..));
+		debug.field("suspend", &self.suspend.as_ref().map(|_| ..));
+		debug.field("transform", &self.transform.as_ref().map(|_| ..));
+		debug.field("on_error", &self.on_error.as_ref().map(|_| ..));
+		debug.field("static_fields", &self.static_fields);
+		#[cfg(feature = "kv")]
+		debug.field("kv_position", &self.kv_position);
+		#[cfg(feature = "tracing")]
+		debug.field("max_field_len", &self.max_field_len);
+		#[cfg(feature = "tracing")]
+		debug.field("max_fields", &self.max_fields);
+		#[cfg(feature = "tracing")]
+		debug.field("field_style", &self.field_style);
+		#[cfg(feature = "tracing")]
+		debug.field("include_span_id", &self.include_span_id);
+		#[cfg(feature = "tracing")]
+		debug.field("dedup_fields", &self.dedup_fields);
+		#[cfg(feature = "tracing")]
+		debug.field("span_timings", &self.span_timings);
+		#[cfg(feature = "tracing")]
+		debug.field("span_name_style", &self.span_name_style);
+		#[cfg(feature = "tracing")]
+		debug.field("dim", &self.dim.as_ref().map(|_| ..));
+		debug.finish()
+	}
+}
+
+/// How records are separated from each other in the output.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+	/// End each record with `\n`.
+	#[default]
+	Newline,
+
+	/// End each record with `\0`.
+	Null,
+
+	/// Don't separate records at all. Useful if the output frames messages itself.
+	None,
+
+	/// Prefix each record with its length in bytes, as a big-endian [`u32`]. Records longer than
+	/// [`u32::MAX`] bytes are skipped, and passed to [`on_error`](Logger::on_error).
+	LengthPrefixed,
+}
+
+impl Framing {
+	fn write<W: io::Write>(self, writer: &mut W, record: &mut String) -> io::Result<()> {
+		match self {
+			Framing::Newline => record.push('\n'),
+			Framing::Null => record.push('\0'),
+			Framing::None => (),
+			Framing::LengthPrefixed => {
+				let len = u32::try_from(record.len()).map_err(|_| {
+					io::Error::new(
+						io::ErrorKind::InvalidInput,
+						"record is too long for a length prefix",
+					)
+				})?;
+				// one write per record, so a writer that drops whole writes can't split a frame
+				let mut frame = Vec::with_capacity(4 + record.len());
+				frame.extend_from_slice(&len.to_be_bytes());
+				frame.extend_from_slice(record.as_bytes());
+				return writer.write_all(&frame);
+			},
+		}
+		writer.write_all(record.as_bytes())
+	}
+}
+
+/// How much of a record's module path to show.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PathDepth {
+	/// The whole path, e.g. `myapp/storage/db`.
+	#[default]
+	Full,
+
+	/// Only the last segment, e.g. `db`.
+	LeafOnly,
+}
+
+/// How timestamps are written.
+#[cfg(feature = "timestamps")]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampFormat {
+	/// Easy to read, like `2:05:09-PM-2024/03/07`, and configurable with the other timestamp
+	/// options on [`Logger`].
+	#[default]
+	Pretty,
+
+	/// [RFC 3339](https://www.rfc-editor.org/rfc/rfc3339), like `2024-03-07T14:05:09Z` or
+	/// `2024-03-07T14:05:09+05:30`, for tools that need to parse it.
+	Rfc3339,
+
+	/// Time since the first record with a timestamp, like `[  12.034s]`. Ignores the other
+	/// timestamp options on [`Logger`].
+	Elapsed,
+}
+
+/// How much of the date timestamps show.
+#[cfg(feature = "timestamps")]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateDisplay {
+	/// Like `2026/01/02`.
+	#[default]
+	Full,
+
+	/// Like `01/02`.
+	MonthDay,
+
+	/// Only show the time.
+	None,
+}
+
+/// How the level is shown.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelStyle {
+	/// Bold and underlined, in the level's color.
+	#[default]
+	Underline,
+
+	/// Inside a "chip" with the level's color as the background, like ` error `.
+	Badge,
+}
+
+/// How a `tracing` event's fields are laid out.
+#[cfg(feature = "tracing")]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldStyle {
+	/// Each field on its own indented line, like `count: 3`.
+	#[default]
+	Multiline,
+
+	/// Space-separated on the same line as the prefix, like `count=3`, matching the default format
+	/// of [`tracing_subscriber::fmt`](https://docs.rs/tracing-subscriber/latest/tracing_subscriber/fmt).
+	///
+	/// Span fields are still shown on their own lines.
+	TracingDefault,
+}
+
+/// Where a `log` record's key-value pairs go, relative to its message.
+#[cfg(feature = "kv")]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldPosition {
+	/// Before the message, as context for it.
+	BeforeMessage,
+
+	/// After the message.
+	#[default]
+	AfterMessage,
+}
+
+/// How a record's location is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LineFormat {
+	/// Show the source file (`src/db.rs:12`) instead of the module path (`myapp/db:12`), when it's
+	/// known.
+	///
+	/// Defaults to [`false`](bool).
+	pub file: bool,
+
+	/// Zero-pad line numbers to this many digits, so they don't jitter. Longer line numbers are
+	/// printed in full.
+	///
+	/// Defaults to `0`.
+	pub width: usize,
+}
+
+/// Limits how often records of one level are written.
+#[derive(Debug)]
+struct Throttle {
+	interval: Option<Duration>,
+	/// Nanoseconds between `Logger::created` and the last record that got through, or [`u64::MAX`]
+	/// if there wasn't one.
+	last: AtomicU64,
+	/// How many records were dropped since the last one that got through.
+	suppressed: AtomicU64,
+}
+
+impl Default for Throttle {
+	fn default() -> Self {
+		Self {
+			interval: None,
+			last: AtomicU64::new(u64::MAX),
+			suppressed: AtomicU64::new(0),
+		}
+	}
+}
+
+/// A new [`Logger::id`].
+#[cfg(feature = "tracing")]
+fn next_id() -> usize {
+	static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+	NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Everything that should only be touched while holding the output lock.
+#[derive(Debug)]
+struct Output<T> {
+	writer: T,
+	last_level: Option<Level>,
+}
+
+impl Logger<io::Sink> {
+	/// Create a [`Logger`] that discards everything.
+	///
+	/// Unlike a [`Logger`] that writes to [`io::Sink`], this skips formatting entirely.
+	pub fn null() -> Self {
+		let mut logger = Self::new(io::sink());
+		logger.discard = true;
+		logger
+	}
+}
+
+impl<T: io::Write + Send + Sync + 'static> Logger<io::BufWriter<T>> {
+	/// Create a [`Logger`] that buffers its output, to make fewer writes when there are a lot of
+	/// records.
+	///
+	/// The output is flushed after warnings and errors (see [`flush_on`](Self::flush_on)), when
+	/// flushed through `log`, and when the logger is dropped.
+	pub fn buffered(output: T) -> Self {
+		let mut logger = Self::new(io::BufWriter::new(output));
+		logger.flush_on = LevelFilter::Warn;
+		logger
+	}
+
+	/// [`auto_line_buffer`](Self::auto_line_buffer), with a different way to tell if the output is a
+	/// terminal.
+	fn auto_line_buffer_with<F: FnOnce(&T) -> bool>(&mut self, enabled: bool, is_terminal: F) {
+		let interactive = enabled && is_terminal(self.lock_output().writer.get_ref());
+		self.flush_on = if interactive {
+			LevelFilter::Trace
+		} else {
+			LevelFilter::Off
+		};
+	}
+}
+
+impl<T: io::Write + IsTerminal + Send + Sync + 'static> Logger<io::BufWriter<T>> {
+	/// Flush after every record if the output is a terminal, but leave it fully buffered otherwise,
+	/// like C's stdio does. Useful with [`Logger::buffered`].
+	///
+	/// This sets [`flush_on`](Self::flush_on) to [`LevelFilter::Trace`] when enabled and the output
+	/// is a terminal, and to [`LevelFilter::Off`] when it isn't.
+	pub fn auto_line_buffer(&mut self, enabled: bool) {
+		self.auto_line_buffer_with(enabled, T::is_terminal);
+	}
+}
+
+impl Logger<io::Stdout> {
+	/// Whether color should be enabled according to the environment, for any output, e.g.
+	/// `logger.color = Logger::color_from_env()`.
+	///
+	/// In order:
+	/// - If `NO_COLOR` is set (and not empty), color is disabled.
+	/// - If `CLICOLOR_FORCE` is set (and not empty or `0`), color is enabled.
+	/// - Otherwise, if `detect-color` is enabled, whether [`io::Stdout`] supports color. If it
+	///   isn't, color is disabled.
+	pub fn color_from_env() -> bool {
+		if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+			return false;
+		}
+		if env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+			return true;
+		}
+
+		#[cfg(feature = "detect-color")]
+		return supports_color::on(supports_color::Stream::Stdout)
+			.is_some_and(|support| support.has_basic);
+		#[cfg(not(feature = "detect-color"))]
+		return false;
+	}
 }
 
 impl Default for Logger<io::Stdout> {
@@ -73,9 +770,59 @@ impl Default for Logger<io::Stdout> {
 	}
 }
 
+/// Lock a [`Mutex`], even if it's poisoned.
+fn lock<U: ?Sized>(mutex: &Mutex<U>) -> MutexGuard<'_, U> {
+	#[allow(unused_mut)]
+	let mut guard = mutex.lock();
+	#[cfg(not(feature = "parking_lot"))]
+	let guard = guard.unwrap_or_else(|e| e.into_inner());
+	guard
+}
+
+/// The level of the longest `prefix` that matches `target`, see [`Logger::target_levels`].
+fn target_filter(filters: &[(String, LevelFilter)], target: &str) -> Option<LevelFilter> {
+	filters
+		.iter()
+		.filter(|(prefix, _)| {
+			target
+				.strip_prefix(prefix.as_str())
+				.is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+		})
+		.max_by_key(|(prefix, _)| prefix.len())
+		.map(|&(_, filter)| filter)
+}
+
+fn push_suppressed<S: StringLike>(output: &mut S, suppressed: u64) {
+	if suppressed > 0 {
+		output.push('\n');
+		output.push_str("(+");
+		output.push_str(itoa::Buffer::new().format(suppressed));
+		output.push_str(" suppressed)");
+	}
+}
+
+fn level_name(level: Level) -> &'static str {
+	match level {
+		Level::Trace => "trace",
+		Level::Debug => "debug",
+		Level::Info => "info",
+		Level::Warn => "warn",
+		Level::Error => "error",
+	}
+}
+
 struct PrefixOptions {
 	align: bool,
 
+	/// Keep the rest of the record dim after the prefix.
+	dim: bool,
+
+	/// Time since the previous record.
+	delta: Option<Duration>,
+
+	/// An icon and color to use instead of the level's.
+	icon: Option<(&'static str, Color)>,
+
 	#[cfg(feature = "timestamps")]
 	time: Option<SystemTime>,
 }
@@ -83,30 +830,718 @@ struct PrefixOptions {
 impl<T: io::Write + Send + Sync + 'static> Logger<T> {
 	/// Create a new [`Logger`].
 	///
-	/// # Panics
-	/// Panics if there was an error getting the local timezone.
-	/// (Only if `detect-timezone` is enabled).
+	/// This never panics. If color support or the local timezone can't be detected, color is
+	/// disabled and the timezone falls back to UTC.
 	pub fn new(output: T) -> Self {
+		#[cfg(feature = "detect-color")]
+		let support = supports_color::on(supports_color::Stream::Stdout);
+		#[cfg(feature = "detect-color")]
+		let color_depth = match support {
+			Some(support) if support.has_16m => ColorDepth::TrueColor,
+			Some(support) if support.has_256 => ColorDepth::Ansi256,
+			_ => ColorDepth::Basic,
+		};
+		#[cfg(not(feature = "detect-color"))]
+		let color_depth = ColorDepth::Basic;
+
 		Self {
-			output: Mutex::new(output),
+			output: Mutex::new(Output {
+				writer: output,
+				last_level: None,
+			}),
+			discard: false,
+			channel: None,
+			created: Instant::now(),
+			last_record: AtomicU64::new(u64::MAX),
+			last_notification: AtomicU64::new(u64::MAX),
+			counts: Default::default(),
+			throttles: Default::default(),
+			routes: Default::default(),
+			#[cfg(feature = "tracing")]
+			id: next_id(),
+			#[cfg(feature = "timestamps")]
+			last_timestamp: AtomicI64::new(i64::MIN),
+			#[cfg(feature = "timestamps")]
+			last_date: AtomicI32::new(i32::MIN),
+			#[cfg(feature = "timestamps")]
+			first_timestamp: OnceLock::new(),
+
+			level: AtomicU8::new(LevelFilter::Trace as u8),
+			boosts: Mutex::new(Vec::new()),
+			max_boost: AtomicU8::new(LevelFilter::Off as u8),
+			target_levels: Vec::new(),
 
 			#[cfg(not(feature = "detect-color"))]
 			color: false,
 
 			#[cfg(feature = "detect-color")]
-			color: supports_color::on(supports_color::Stream::Stdout)
-				.map(|i| i.has_basic)
-				.unwrap_or(false),
+			color: support.is_some_and(|support| support.has_basic),
+
+			color_depth,
+			theme: Theme::for_depth(color_depth),
 
 			#[cfg(all(feature = "timestamps", feature = "detect-timezone"))]
-			timezone: time::UtcOffset::current_local_offset()
-				.expect("failed to get local utc offset"),
+			timezone: time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC),
 
 			#[cfg(all(feature = "timestamps", not(feature = "detect-timezone")))]
 			timezone: time::UtcOffset::UTC,
+
+			#[cfg(feature = "timestamps")]
+			timestamp_from: LevelFilter::Trace,
+			#[cfg(feature = "timestamps")]
+			timestamp_format: TimestampFormat::Pretty,
+			#[cfg(feature = "timestamps")]
+			time_24h: false,
+			#[cfg(feature = "timestamps")]
+			date_display: DateDisplay::Full,
+			#[cfg(feature = "timestamps")]
+			date_time_separator: '-',
+			#[cfg(feature = "timestamps")]
+			date_separator: '/',
+			#[cfg(feature = "timestamps")]
+			show_timezone: false,
+			#[cfg(feature = "timestamps")]
+			collapse_timestamps: false,
+			#[cfg(feature = "timestamps")]
+			collapse_dates: false,
+
+			highlight_escalation: false,
+			labels: Level::ALL.map(level_name),
+			show_icon: true,
+			level_style: LevelStyle::Underline,
+			show_delta: false,
+			icons: IconSet::default(),
+			flush_on: LevelFilter::Off,
+			single_line: false,
+			prefix_content_separator: None,
+			page_long_content: false,
+			page_threshold: None,
+			notify_on: LevelFilter::Off,
+			frame: Framing::Newline,
+			path_depth: PathDepth::Full,
+			module_fallback: None,
+			line_format: LineFormat::default(),
+			target_color: None,
+			suspend: None,
+			transform: None,
+			on_error: None,
+			static_fields: Vec::new(),
+
+			#[cfg(feature = "kv")]
+			kv_position: FieldPosition::AfterMessage,
+
+			#[cfg(feature = "tracing")]
+			max_field_len: None,
+
+			#[cfg(feature = "tracing")]
+			max_fields: None,
+
+			#[cfg(feature = "tracing")]
+			field_style: FieldStyle::Multiline,
+
+			#[cfg(feature = "tracing")]
+			include_span_id: false,
+
+			#[cfg(feature = "tracing")]
+			dedup_fields: false,
+
+			#[cfg(feature = "tracing")]
+			span_timings: false,
+
+			#[cfg(feature = "tracing")]
+			span_name_style: Style::bold(),
+
+			#[cfg(feature = "tracing")]
+			dim: None,
+		}
+	}
+
+	/// Create a [`Logger`] with the same configuration as this one, but writing to `output`.
+	///
+	/// Things that aren't configuration, like [`counts`](Self::log_summary) and the previous record,
+	/// start over. [Routes](Self::route) can't be copied, so everything goes to `output`, even if
+	/// this is a [`Logger::null`] or a [`channel_sink`].
+	pub fn clone_with_output<U: io::Write + Send + Sync + 'static>(&self, output: U) -> Logger<U> {
+		Logger {
+			output: Mutex::new(Output {
+				writer: output,
+				last_level: None,
+			}),
+			discard: false,
+			channel: None,
+			created: Instant::now(),
+			last_record: AtomicU64::new(u64::MAX),
+			last_notification: AtomicU64::new(u64::MAX),
+			counts: Default::default(),
+			throttles: self.throttles.each_ref().map(|throttle| Throttle {
+				interval: throttle.interval,
+				..Default::default()
+			}),
+			routes: Default::default(),
+			#[cfg(feature = "tracing")]
+			id: next_id(),
+			#[cfg(feature = "timestamps")]
+			last_timestamp: AtomicI64::new(i64::MIN),
+			#[cfg(feature = "timestamps")]
+			last_date: AtomicI32::new(i32::MIN),
+			#[cfg(feature = "timestamps")]
+			first_timestamp: OnceLock::new(),
+
+			level: AtomicU8::new(self.level() as u8),
+			boosts: Mutex::new(lock(&self.boosts).clone()),
+			max_boost: AtomicU8::new(self.max_boost.load(Ordering::Relaxed)),
+			target_levels: self.target_levels.clone(),
+			color: self.color,
+			color_depth: self.color_depth,
+			theme: self.theme,
+			#[cfg(feature = "timestamps")]
+			timezone: self.timezone,
+			#[cfg(feature = "timestamps")]
+			timestamp_from: self.timestamp_from,
+			#[cfg(feature = "timestamps")]
+			timestamp_format: self.timestamp_format,
+			#[cfg(feature = "timestamps")]
+			time_24h: self.time_24h,
+			#[cfg(feature = "timestamps")]
+			date_display: self.date_display,
+			#[cfg(feature = "timestamps")]
+			date_time_separator: self.date_time_separator,
+			#[cfg(feature = "timestamps")]
+			date_separator: self.date_separator,
+			#[cfg(feature = "timestamps")]
+			show_timezone: self.show_timezone,
+			#[cfg(feature = "timestamps")]
+			collapse_timestamps: self.collapse_timestamps,
+			#[cfg(feature = "timestamps")]
+			collapse_dates: self.collapse_dates,
+			highlight_escalation: self.highlight_escalation,
+			icons: self.icons,
+			labels: self.labels,
+			show_icon: self.show_icon,
+			level_style: self.level_style,
+			show_delta: self.show_delta,
+			flush_on: self.flush_on,
+			single_line: self.single_line,
+			prefix_content_separator: self.prefix_content_separator,
+			page_long_content: self.page_long_content,
+			page_threshold: self.page_threshold,
+			notify_on: self.notify_on,
+			frame: self.frame,
+			path_depth: self.path_depth,
+			module_fallback: self.module_fallback,
+			line_format: self.line_format,
+			target_color: self.target_color.clone(),
+			suspend: self.suspend.clone(),
+			transform: self.transform.clone(),
+			on_error: self.on_error.clone(),
+			static_fields: self.static_fields.clone(),
+			#[cfg(feature = "kv")]
+			kv_position: self.kv_position,
+			#[cfg(feature = "tracing")]
+			max_field_len: self.max_field_len,
+			#[cfg(feature = "tracing")]
+			max_fields: self.max_fields,
+			#[cfg(feature = "tracing")]
+			field_style: self.field_style,
+			#[cfg(feature = "tracing")]
+			include_span_id: self.include_span_id,
+			#[cfg(feature = "tracing")]
+			dedup_fields: self.dedup_fields,
+			#[cfg(feature = "tracing")]
+			span_timings: self.span_timings,
+			#[cfg(feature = "tracing")]
+			span_name_style: self.span_name_style,
+			#[cfg(feature = "tracing")]
+			dim: self.dim.clone(),
 		}
 	}
 
+	/// Set [`suspend`](Self::suspend).
+	pub fn set_suspend<F>(&mut self, suspend: F)
+	where
+		F: Fn(&mut dyn FnMut()) + Send + Sync + 'static,
+	{
+		self.suspend = Some(Arc::new(suspend));
+	}
+
+	/// Write at most one record at `level` per `interval`, e.g. so an error storm doesn't flood the
+	/// output.
+	///
+	/// Records in between are dropped, and the next one that gets through says how many were, like
+	/// `(+12 suppressed)`.
+	pub fn throttle(&mut self, level: Level, interval: Duration) {
+		self.throttles[level as usize].interval = Some(interval);
+	}
+
+	/// Write records at `level` to `writer` instead of the output, e.g. to send `trace` records to a
+	/// file. Each writer has its own lock.
+	///
+	/// Routed records aren't [paged](Self::page_long_content), don't go through
+	/// [`suspend`](Self::suspend), and don't count towards
+	/// [`highlight_escalation`](Self::highlight_escalation) or [`notify_on`](Self::notify_on).
+	pub fn route<W: io::Write + Send + 'static>(&mut self, level: Level, writer: W) {
+		self.routes[level as usize] = Some(Mutex::new(Box::new(writer)));
+	}
+
+	/// The most verbose level to write, see [`set_level`](Self::set_level).
+	pub fn level(&self) -> LevelFilter {
+		LevelFilter::ALL[usize::from(self.level.load(Ordering::Relaxed))]
+	}
+
+	/// Set the most verbose level to write. Records that are filtered out aren't formatted at all.
+	///
+	/// This takes `&self`, so it can be changed after the logger is installed, e.g. on a signal.
+	/// Defaults to [`LevelFilter::Trace`].
+	pub fn set_level(&self, level: LevelFilter) {
+		self.level.store(level as u8, Ordering::Relaxed);
+	}
+
+	/// Use `level` for records from `target` and its submodules, see
+	/// [`target_levels`](Self::target_levels).
+	pub fn add_target_level(&mut self, target: impl Into<String>, level: LevelFilter) {
+		self.target_levels.push((target.into(), level));
+	}
+
+	/// Temporarily write records from `target` and its submodules down to `level`, e.g. to debug
+	/// one subsystem without restarting. Boosting a target again replaces its level.
+	///
+	/// Like [`set_level`](Self::set_level), this takes `&self`. A boost never hides anything: a
+	/// record is written if either the boost or [`level`](Self::level) and
+	/// [`target_levels`](Self::target_levels) allow it. See also [`unboost`](Self::unboost).
+	pub fn boost(&self, target: impl Into<String>, level: LevelFilter) {
+		let target = target.into();
+		let mut boosts = lock(&self.boosts);
+		match boosts.iter_mut().find(|(prefix, _)| *prefix == target) {
+			Some((_, old)) => *old = level,
+			None => boosts.push((target, level)),
+		}
+		self.store_max_boost(&boosts);
+	}
+
+	/// Remove the [`boost`](Self::boost) for `target`, if there is one.
+	pub fn unboost(&self, target: &str) {
+		let mut boosts = lock(&self.boosts);
+		boosts.retain(|(prefix, _)| prefix != target);
+		self.store_max_boost(&boosts);
+	}
+
+	fn store_max_boost(&self, boosts: &[(String, LevelFilter)]) {
+		let max = boosts.iter().map(|&(_, level)| level).max();
+		let max = max.unwrap_or(LevelFilter::Off);
+		self.max_boost.store(max as u8, Ordering::Relaxed);
+	}
+
+	/// Add fields to every record this logger writes, see [`static_fields`](Self::static_fields).
+	pub fn with_fields(mut self, fields: &[(&str, &str)]) -> Self {
+		self.static_fields.extend(
+			fields
+				.iter()
+				.map(|&(name, value)| (name.to_owned(), value.to_owned())),
+		);
+		self
+	}
+
+	/// Set the [`timezone`](Self::timezone) from a string like `+05:30`, `-0800`, `Z`, or `UTC`.
+	#[cfg(feature = "timestamps")]
+	pub fn set_timezone_str(&mut self, timezone: &str) -> Result<(), ParseTimezoneError> {
+		self.timezone = timezone::parse_offset(timezone)?;
+		Ok(())
+	}
+
+	/// Run `f`, then log how long it took at the given level.
+	///
+	/// Logs `{label} completed in {time}`, or `{label} failed after {time}` if `f` returns an error.
+	/// `target` is what filters go by, usually `module_path!()`. The record's location is the
+	/// caller's file and line.
+	#[track_caller]
+	pub fn timed<R, E, F>(&self, level: Level, target: &str, label: &str, f: F) -> Result<R, E>
+	where
+		F: FnOnce() -> Result<R, E>,
+	{
+		let location = Location::caller();
+		let start = Instant::now();
+		let result = f();
+		let elapsed = start.elapsed();
+
+		let outcome = if result.is_ok() {
+			"completed in"
+		} else {
+			"failed after"
+		};
+		self.write_record(&Metadata::caller(level, target, location), None, |output| {
+			push_message(output, format_args!("{label} {outcome} {elapsed:.1?}"))
+		});
+		result
+	}
+
+	/// Log a success message, like `info` but with a green `✔` icon.
+	///
+	/// `target` is what filters go by, usually `module_path!()`. The record's location is the
+	/// caller's file and line.
+	#[track_caller]
+	pub fn success(&self, target: &str, args: fmt::Arguments) {
+		let location = Location::caller();
+		self.write_record(
+			&Metadata::caller(Level::Info, target, location),
+			Some(("✔", Color::Green)),
+			|output| push_message(output, args),
+		);
+	}
+
+	/// Whether a record at `level` from `target` would be written, without formatting anything.
+	///
+	/// Useful to skip building expensive log arguments, like `log::log_enabled!`, but this also works
+	/// for `tracing`, and for records written straight to the [`Logger`].
+	pub fn is_enabled(&self, level: Level, target: &str) -> bool {
+		if (self.discard && self.channel.is_none()) || !STATIC_MAX_LEVEL.allows(level) {
+			return false;
+		}
+		let filter = target_filter(&self.target_levels, target).unwrap_or_else(|| self.level());
+		if filter.allows(level) {
+			return true;
+		}
+		let max_boost = LevelFilter::ALL[usize::from(self.max_boost.load(Ordering::Relaxed))];
+		max_boost.allows(level)
+			&& target_filter(&lock(&self.boosts), target).is_some_and(|boost| boost.allows(level))
+	}
+
+	/// Log that something changed, like `retries: 1 → 2`.
+	///
+	/// `target` is what filters go by, usually `module_path!()`. The record's location is the
+	/// caller's file and line.
+	#[track_caller]
+	pub fn log_change<V: fmt::Debug + ?Sized>(
+		&self,
+		level: Level,
+		target: &str,
+		label: &str,
+		old: &V,
+		new: &V,
+	) {
+		let location = Location::caller();
+		self.write_record(&Metadata::caller(level, target, location), None, |output| {
+			push_message(output, format_args!("{label}: {old:?} → {new:?}"))
+		});
+	}
+
+	/// Write a pre-formatted line directly to the output, without any prefix or formatting.
+	///
+	/// This goes through the same lock as regular records, so it won't interleave with them. It's
+	/// flushed unless [`flush_on`](Self::flush_on) is [`LevelFilter::Off`].
+	pub fn write_line(&self, line: &str) {
+		if self.discard {
+			return;
+		}
+		with_local_buf(move |buf| {
+			buf.clear();
+			buf.push_str(line);
+			self.write_output(buf, None);
+		})
+	}
+
+	/// Write a dim horizontal line across the terminal, optionally with a title in the middle.
+	///
+	/// The width comes from `$COLUMNS`, falling back to 80.
+	pub fn rule(&self, title: Option<&str>) {
+		if self.discard {
+			return;
+		}
+		with_local_buf(move |buf| {
+			buf.clear();
+			let width = terminal_width();
+			if self.color {
+				// dim
+				buf.push_str("\x1b[2m");
+			}
+			match title {
+				Some(title) => {
+					let rest = width.saturating_sub(display_width(title) + 2);
+					buf.extend(std::iter::repeat_n('─', rest / 2));
+					buf.push(' ');
+					buf.push_str(title);
+					buf.push(' ');
+					buf.extend(std::iter::repeat_n('─', rest - rest / 2));
+				},
+				None => buf.extend(std::iter::repeat_n('─', width)),
+			}
+			if self.color {
+				// reset
+				buf.push_str("\x1b[m");
+			}
+			self.write_output(buf, None);
+		})
+	}
+
+	/// Write how many records were written at each level, like
+	/// `summary: 3 errors, 12 warnings, 540 info`. Useful to call right before the program exits.
+	///
+	/// Levels without any records are left out.
+	pub fn log_summary(&self) {
+		if self.discard {
+			return;
+		}
+		with_local_buf(move |buf| {
+			buf.clear();
+			buf.push_str("summary:");
+			let mut any = false;
+			for &level in Level::ALL.iter().rev() {
+				let count = self.counts[level as usize].load(Ordering::Relaxed);
+				if count == 0 {
+					continue;
+				}
+				buf.push_str(if any { ", " } else { " " });
+				any = true;
+				buf.push_str(itoa::Buffer::new().format(count));
+				buf.push(' ');
+				buf.push_str(match level {
+					Level::Error if count != 1 => "errors",
+					Level::Warn if count != 1 => "warnings",
+					Level::Error => "error",
+					Level::Warn => "warning",
+					_ => level_name(level),
+				});
+			}
+			if !any {
+				buf.push_str(" no records");
+			}
+			self.write_output(buf, None);
+		})
+	}
+
+	fn lock_output(&self) -> MutexGuard<'_, Output<T>> {
+		lock(&self.output)
+	}
+
+	/// Time since the previous record, if [`show_delta`](Self::show_delta) is enabled.
+	fn delta(&self) -> Option<Duration> {
+		if !self.show_delta {
+			return None;
+		}
+		let now = u64::try_from(self.created.elapsed().as_nanos()).unwrap_or(u64::MAX - 1);
+		let last = self.last_record.swap(now, Ordering::Relaxed);
+		if last == u64::MAX {
+			return Some(Duration::ZERO);
+		}
+		Some(Duration::from_nanos(now.saturating_sub(last)))
+	}
+
+	/// Check [`throttle`](Self::throttle). Returns how many records were suppressed before this one,
+	/// or [`None`] if this one should be suppressed too.
+	fn check_throttle(&self, level: Level) -> Option<u64> {
+		let throttle = &self.throttles[level as usize];
+		let Some(interval) = throttle.interval else {
+			return Some(0);
+		};
+		let now = u64::try_from(self.created.elapsed().as_nanos()).unwrap_or(u64::MAX - 1);
+		let last = throttle.last.load(Ordering::Relaxed);
+		let interval = u64::try_from(interval.as_nanos()).unwrap_or(u64::MAX);
+		if (last != u64::MAX && now.saturating_sub(last) < interval)
+			|| throttle
+				.last
+				.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+				.is_err()
+		{
+			throttle.suppressed.fetch_add(1, Ordering::Relaxed);
+			return None;
+		}
+		Some(throttle.suppressed.swap(0, Ordering::Relaxed))
+	}
+
+	/// Write a record with the standard prefix, where `content` writes everything after it.
+	fn write_record<F>(&self, meta: &Metadata, icon: Option<(&'static str, Color)>, content: F)
+	where
+		F: FnOnce(&mut Indented<&mut String>),
+	{
+		if self.is_enabled(meta.level, meta.target) {
+			self.write_record_at(meta, icon, SystemTime::now(), content);
+		}
+	}
+
+	/// Like [`write_record`](Self::write_record), but for a record made at `time` that the caller
+	/// already checked with [`is_enabled`](Self::is_enabled).
+	fn write_record_at<F>(
+		&self,
+		meta: &Metadata,
+		icon: Option<(&'static str, Color)>,
+		time: SystemTime,
+		content: F,
+	) where
+		F: FnOnce(&mut Indented<&mut String>),
+	{
+		if self.channel.is_some() {
+			let mut message = String::new();
+			content(&mut Indented::new(&mut message, 0));
+			if message.starts_with('\n') {
+				message.remove(0);
+			}
+			self.send_record(meta, time, message, Vec::new());
+			return;
+		}
+		let Some(suppressed) = self.check_throttle(meta.level) else {
+			return;
+		};
+		let delta = self.delta();
+
+		with_local_buf(move |mut buf| {
+			buf.clear();
+
+			self.write_prefix(
+				&mut buf,
+				meta,
+				&PrefixOptions {
+					align: true,
+					dim: false,
+					delta,
+					icon,
+					#[cfg(feature = "timestamps")]
+					time: self.timestamp_from.allows(meta.level).then_some(time),
+				},
+			);
+			let prefix_len = buf.len();
+
+			let mut indented = Indented::new(&mut *buf, 8);
+			indented.escape = self.single_line;
+			content(&mut indented);
+			self.push_static_fields(&mut indented);
+			push_suppressed(&mut indented, suppressed);
+			self.inline_content(buf, prefix_len);
+
+			if let Some(transform) = &self.transform {
+				transform(buf);
+			}
+			self.write_output(buf, Some(meta.level));
+		})
+	}
+
+	/// If [`prefix_content_separator`](Self::prefix_content_separator) is set and everything after
+	/// the prefix is a single line, move it up onto the prefix's line.
+	fn inline_content(&self, buf: &mut String, prefix_len: usize) {
+		const LINE_START: &str = "\n        ";
+		let Some(separator) = self.prefix_content_separator else {
+			return;
+		};
+		let content = &buf[prefix_len..];
+		if content.starts_with(LINE_START) && !content[LINE_START.len()..].contains('\n') {
+			buf.replace_range(prefix_len..prefix_len + LINE_START.len(), separator);
+		}
+	}
+
+	fn push_static_fields<S: StringLike + fmt::Write>(&self, output: &mut S) {
+		for (name, value) in &self.static_fields {
+			output.push('\n');
+			output.push_str(name);
+			output.push_str(": ");
+			write_args(output, format_args!("{value:?}"));
+		}
+	}
+
+	/// Write a record to the output. The record's terminator is added here.
+	fn write_output(&self, buf: &mut String, level: Option<Level>) {
+		if let Some((level, route)) =
+			level.and_then(|level| Some((level, self.routes[level as usize].as_ref()?)))
+		{
+			self.counts[level as usize].fetch_add(1, Ordering::Relaxed);
+			let mut writer = lock(route);
+			self.handle_error(self.frame.write(&mut *writer, buf));
+			if self.flush_on.allows(level) {
+				self.handle_error(writer.flush());
+			}
+			return;
+		}
+
+		// the pager stays open as long as the user wants, so don't hold the lock for it
+		let mut paged = false;
+		if self.page_long_content
+			&& level.is_some()
+			&& pager::stdout_is_terminal()
+			&& buf.lines().count() > self.page_threshold.unwrap_or_else(terminal_height)
+		{
+			let mut page = || paged = pager::page(buf);
+			match &self.suspend {
+				Some(suspend) => suspend(&mut page),
+				None => page(),
+			}
+		}
+
+		let mut output = self.lock_output();
+
+		let mut write = || {
+			if let Some(level) = level {
+				self.counts[level as usize].fetch_add(1, Ordering::Relaxed);
+				if self.highlight_escalation && output.last_level.is_some_and(|last| level > last) {
+					let mut rule = String::from(if self.color {
+						// dim
+						"\x1b[2m────────────────────────────────────────\x1b[m"
+					} else {
+						"────────────────────────────────────────"
+					});
+					self.handle_error(self.frame.write(&mut output.writer, &mut rule));
+				}
+				output.last_level = Some(level);
+			}
+
+			if !paged {
+				self.handle_error(self.frame.write(&mut output.writer, buf));
+			}
+
+			if level.is_some_and(|level| self.notify_on.allows(level)) {
+				self.handle_error(self.notify(&mut output.writer, buf));
+			}
+
+			// lines without a level might be anything, so they're flushed unless flushing is off
+			let flush = match level {
+				Some(level) => self.flush_on.allows(level),
+				None => self.flush_on != LevelFilter::Off,
+			};
+			if flush {
+				self.handle_error(output.writer.flush());
+			}
+		};
+
+		match &self.suspend {
+			Some(suspend) => suspend(&mut write),
+			None => write(),
+		}
+	}
+
+	/// Pass an error to [`on_error`](Self::on_error).
+	fn handle_error(&self, result: io::Result<()>) {
+		if let (Err(error), Some(on_error)) = (result, &self.on_error) {
+			on_error(&error);
+		}
+	}
+
+	/// Send an OSC 9 notification for a record, see [`notify_on`](Self::notify_on).
+	fn notify(&self, writer: &mut T, record: &str) -> io::Result<()> {
+		// anything other than newlines is meant for a program, which would see this as a broken
+		// frame
+		if !self.color || self.frame != Framing::Newline {
+			return Ok(());
+		}
+		let now = u64::try_from(self.created.elapsed().as_nanos()).unwrap_or(u64::MAX - 1);
+		let last = self.last_notification.load(Ordering::Relaxed);
+		if last != u64::MAX && now.saturating_sub(last) < 1_000_000_000
+			|| self
+				.last_notification
+				.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed)
+				.is_err()
+		{
+			return Ok(());
+		}
+
+		// the prefix and the first line of content, without the colors or anything that would end
+		// the sequence early
+		let mut text = String::new();
+		for line in record.lines().take(2) {
+			if !text.is_empty() {
+				text.push_str(": ");
+			}
+			text.push_str(strip_ansi(line).trim());
+		}
+		text.retain(|c| !c.is_control());
+		writer.write_all(format!("\x1b]9;{text}\x07").as_bytes())
+	}
+
 	fn write_prefix<S: StringLike>(
 		&self,
 		output: &mut S,
@@ -115,49 +1550,123 @@ impl<T: io::Write + Send + Sync + 'static> Logger<T> {
 	) {
 		let color = self.color;
 
-		let (icon, level_str, color_code) = match meta.level {
-			Level::Trace => ('→', "trace", '4'),
-			Level::Debug => ('○', "debug", '6'),
-			Level::Info => ('●', "info", '2'),
-			Level::Warn => ('⚠', "warn", '3'),
-			Level::Error => ('✘', "error", '1'),
-		};
+		let (icon, level_color) = options
+			.icon
+			.unwrap_or_else(|| (self.icons[meta.level], self.theme[meta.level]));
+		let level_color = level_color.downgrade(self.color_depth);
+		let level_str = self.labels[meta.level as usize];
 
-		if options.align && matches!(meta.level, Level::Info | Level::Warn) {
-			output.push(' ');
+		if options.align {
+			// pad shorter level names so the module path always starts at the same column
+			let max_width = self
+				.labels
+				.iter()
+				.map(|label| display_width(label))
+				.max()
+				.unwrap_or(0);
+			for _ in display_width(level_str)..max_width {
+				output.push(' ');
+			}
 		}
 
 		// icon
 		if color {
+			if options.dim {
+				output.push_str("\x1b[2m");
+			}
 			// bright color
-			output.push_str("\x1b[9");
-			output.push(color_code);
+			output.push_str("\x1b[");
+			level_color.push_fg(output, true);
 			output.push('m');
 		}
-		output.push(icon);
-		output.push(' ');
+		if self.show_icon {
+			output.push_str(icon);
+			output.push(' ');
+		}
+		if options.align && self.show_icon {
+			// pad narrower icons so the level always starts at the same column
+			let max_width = Level::ALL
+				.iter()
+				.map(|&level| display_width(self.icons[level]))
+				.max()
+				.unwrap_or(0);
+			for _ in display_width(icon)..max_width {
+				output.push(' ');
+			}
+		}
 
 		// level
-		if color {
-			// bold, underline
-			output.push_str("\x1b[1;4m");
+		match self.level_style {
+			LevelStyle::Badge if color => {
+				output.push_str("\x1b[");
+				level_color.push_bg(output, true);
+				// black or bright white text, whichever is easier to read
+				output.push_str(if level_color.is_light() {
+					";30m "
+				} else {
+					";97m "
+				});
+				output.push_str(level_str);
+				output.push(' ');
+			},
+			_ => {
+				if color {
+					// bold, underline
+					output.push_str("\x1b[1;4m");
+				}
+				output.push_str(level_str);
+			},
 		}
-		output.push_str(level_str);
 		if color {
 			// reset, regular color
-			output.push_str("\x1b[;3");
-			output.push(color_code);
+			output.push_str(if options.dim { "\x1b[;2;" } else { "\x1b[;" });
+			level_color.push_fg(output, false);
 			output.push('m');
 		}
 		output.push(' ');
 
-		let mut module_path_parts = meta.module_path.split("::");
-		if let Some(first_part) = module_path_parts.next() {
-			output.push_str(first_part);
-			for part in module_path_parts {
-				output.push('/');
-				output.push_str(part);
-			}
+		let module_path = meta
+			.module_path
+			.or(self.module_fallback)
+			.unwrap_or(meta.target);
+		let target_color = match &self.target_color {
+			Some(target_color) if color => target_color(module_path)
+				.map(|target_color| target_color.downgrade(self.color_depth)),
+			_ => None,
+		};
+		if let Some(target_color) = target_color {
+			output.push_str("\x1b[");
+			target_color.push_fg(output, false);
+			output.push('m');
+		}
+
+		// not cached, since module paths from `log` aren't guaranteed to be `'static`, so they can't
+		// be keyed by address
+		match (self.path_depth, meta.file) {
+			(_, Some(file)) if self.line_format.file => output.push_str(file),
+			(PathDepth::Full, _) => {
+				// skip empty segments, so a path like `a::b::` doesn't end with a separator
+				let mut module_path_parts = module_path.split("::").filter(|part| !part.is_empty());
+				if let Some(first_part) = module_path_parts.next() {
+					output.push_str(first_part);
+					for part in module_path_parts {
+						output.push('/');
+						output.push_str(part);
+					}
+				}
+			},
+			(PathDepth::LeafOnly, _) => {
+				if let Some(last_part) = module_path.rsplit("::").find(|part| !part.is_empty()) {
+					output.push_str(last_part);
+				}
+			},
+		}
+
+		if target_color.is_some() {
+			// back to the level's color
+			output.push_str("\x1b[");
+			level_color.push_fg(output, false);
+			output.push('m');
 		}
 
 		if let Some(line) = meta.line {
@@ -166,53 +1675,139 @@ impl<T: io::Write + Send + Sync + 'static> Logger<T> {
 				output.push_str("\x1b[2m");
 			}
 			output.push(':');
-			output.push_str(itoa::Buffer::new().format(line));
+			let mut line_buf = itoa::Buffer::new();
+			let line = line_buf.format(line);
+			for _ in line.len()..self.line_format.width {
+				output.push('0');
+			}
+			output.push_str(line);
+		}
+
+		if let Some(delta) = options.delta {
+			output.push(' ');
+			if color {
+				// reset, dim
+				output.push_str("\x1b[;2m");
+			}
+			output.push('+');
+			push_duration(output, delta);
 		}
 
 		#[cfg(feature = "timestamps")]
 		if let Some(time) = options.time {
-			let time = time::OffsetDateTime::from(time).to_offset(self.timezone);
 			output.push(' ');
 			if color {
 				// reset, dim
 				output.push_str("\x1b[;2m");
 			}
 
-			// this is the only place we ever format dates. we don't really need time's formatting feature
-			let mut hour = time.hour();
-			let mut am_or_pm = 'A';
-			if hour >= 12 {
-				am_or_pm = 'P';
-				if hour != 12 {
-					hour -= 12;
+			if self.timestamp_format == TimestampFormat::Elapsed {
+				// whichever thread gets here first sets it
+				let first = *self.first_timestamp.get_or_init(|| time);
+				let elapsed = time.duration_since(first).unwrap_or_default();
+				let seconds = itoa::Buffer::new().format(elapsed.as_secs()).len();
+				output.push('[');
+				for _ in seconds..4 {
+					output.push(' ');
+				}
+				output.push_str(itoa::Buffer::new().format(elapsed.as_secs()));
+				output.push('.');
+				let millis = elapsed.subsec_millis();
+				for _ in itoa::Buffer::new().format(millis).len()..3 {
+					output.push('0');
+				}
+				output.push_str(itoa::Buffer::new().format(millis));
+				output.push_str("s]");
+			} else {
+				let time = time::OffsetDateTime::from(time).to_offset(self.timezone);
+				if self.collapse_timestamps
+					&& self
+						.last_timestamp
+						.swap(time.unix_timestamp(), Ordering::Relaxed)
+						== time.unix_timestamp()
+				{
+					self.write_time(&mut Blank(&mut *output), time);
+				} else {
+					self.write_time(output, time);
 				}
 			}
-			output.push_str(itoa::Buffer::new().format(hour));
-			output.push(':');
-			let minute = time.minute();
-			if minute < 10 {
+		}
+
+		if color {
+			// reset
+			output.push_str(if options.dim { "\x1b[;2m" } else { "\x1b[m" });
+		}
+	}
+
+	#[cfg(feature = "timestamps")]
+	fn write_time<S: StringLike>(&self, output: &mut S, time: time::OffsetDateTime) {
+		if self.timestamp_format == TimestampFormat::Rfc3339 {
+			timezone::push_rfc3339(output, time);
+			return;
+		}
+
+		// this is the only place we ever format dates. we don't really need time's formatting feature
+		let mut hour = time.hour();
+		let mut am_or_pm = 'A';
+		if self.time_24h {
+			if hour < 10 {
 				output.push('0');
 			}
-			output.push_str(itoa::Buffer::new().format(minute));
-			output.push(':');
-			let second = time.second();
-			if second < 10 {
-				output.push('0');
+		} else if hour == 0 {
+			// midnight is 12 AM
+			hour = 12;
+		} else if hour >= 12 {
+			am_or_pm = 'P';
+			if hour != 12 {
+				hour -= 12;
 			}
-			output.push_str(itoa::Buffer::new().format(second));
+		}
+		output.push_str(itoa::Buffer::new().format(hour));
+		output.push(':');
+		let minute = time.minute();
+		if minute < 10 {
+			output.push('0');
+		}
+		output.push_str(itoa::Buffer::new().format(minute));
+		output.push(':');
+		let second = time.second();
+		if second < 10 {
+			output.push('0');
+		}
+		output.push_str(itoa::Buffer::new().format(second));
+		if !self.time_24h {
 			output.push('-');
 			output.push(am_or_pm);
-			output.push_str("M-");
-			output.push_str(itoa::Buffer::new().format(time.year()));
-			output.push('/');
-			output.push_str(itoa::Buffer::new().format(time.month() as u8));
-			output.push('/');
-			output.push_str(itoa::Buffer::new().format(time.day()));
+			output.push('M');
+		}
+		let new_date = !self.collapse_dates
+			|| self.last_date.swap(time.to_julian_day(), Ordering::Relaxed) != time.to_julian_day();
+		if self.date_display != DateDisplay::None && new_date {
+			output.push(self.date_time_separator);
+			if self.date_display == DateDisplay::Full {
+				output.push_str(itoa::Buffer::new().format(time.year()));
+				output.push(self.date_separator);
+			}
+			let month = time.month() as u8;
+			if month < 10 {
+				output.push('0');
+			}
+			output.push_str(itoa::Buffer::new().format(month));
+			output.push(self.date_separator);
+			let day = time.day();
+			if day < 10 {
+				output.push('0');
+			}
+			output.push_str(itoa::Buffer::new().format(day));
 		}
 
-		if color {
-			// reset
-			output.push_str("\x1b[m");
+		if self.show_timezone {
+			output.push(' ');
+			if self.timezone.is_utc() {
+				output.push_str("UTC");
+			} else {
+				timezone::push_offset(output, self.timezone);
+			}
 		}
 	}
 }
@@ -1,63 +1,111 @@
 use crate::{
-	util::{with_local_buf, Indented, StringLike},
-	Logger, PrefixOptions,
+	compat::Metadata,
+	lock,
+	util::{format_lossy, push_message},
+	Logger,
 };
 use log::Log;
-use std::{fmt::Write, io};
+use std::{io, time::SystemTime};
 
-#[cfg(feature = "timestamps")]
-use std::time::SystemTime;
+#[cfg(feature = "kv")]
+use crate::{
+	util::{write_args, StringLike, FMT_ERROR},
+	FieldPosition,
+};
+#[cfg(feature = "kv")]
+use log::kv::{Error, Key, Value, VisitSource};
+#[cfg(feature = "kv")]
+use std::fmt;
 
 impl<T: io::Write + Send + Sync + 'static> Log for Logger<T> {
-	fn enabled(&self, _: &log::Metadata) -> bool {
-		true
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		self.is_enabled(metadata.level().into(), metadata.target())
 	}
 
 	fn flush(&self) {
-		#[allow(unused_mut)]
-		let mut output = self.output.lock();
-		#[cfg(not(feature = "parking_lot"))]
-		let mut output = output.unwrap_or_else(|e| e.into_inner());
-		output.flush().expect("failed to flush log output");
+		self.handle_error(self.lock_output().writer.flush());
+		for route in self.routes.iter().flatten() {
+			self.handle_error(lock(route).flush());
+		}
 	}
 
 	fn log(&self, record: &log::Record) {
-		#[cfg(feature = "timestamps")]
-		let time = SystemTime::now();
-
-		with_local_buf(move |mut buf| {
-			buf.clear();
-
-			self.write_prefix(
-				&mut buf,
-				&record.into(),
-				&PrefixOptions {
-					align: true,
-					#[cfg(feature = "timestamps")]
-					time: Some(time),
-				},
+		let meta = Metadata::from(record);
+		if !self.is_enabled(meta.level, meta.target) {
+			return;
+		}
+		if self.channel.is_some() {
+			#[allow(unused_mut)]
+			let mut fields = Vec::new();
+			#[cfg(feature = "kv")]
+			let _ = record.key_values().visit(&mut KvCollector(&mut fields));
+			self.send_record(
+				&meta,
+				SystemTime::now(),
+				format_lossy(*record.args()),
+				fields,
 			);
+			return;
+		}
+		self.write_record_at(&meta, None, SystemTime::now(), |output| {
+			#[cfg(feature = "kv")]
+			if self.kv_position == FieldPosition::BeforeMessage {
+				push_key_values(output, record);
+			}
 
-			let mut indented = Indented::new(&mut buf, 8);
-			let args = record.args();
-			match args.as_str() {
-				Some(str) if !str.is_empty() => {
-					indented.push('\n');
-					indented.push_str(str);
-				},
-				None => {
-					indented.push('\n');
-					indented.write_fmt(*args).expect("fmt error");
-				},
-				_ => (),
+			push_message(output, *record.args());
+
+			#[cfg(feature = "kv")]
+			if self.kv_position == FieldPosition::AfterMessage {
+				push_key_values(output, record);
 			}
+		});
+	}
+}
 
-			buf.push('\n');
-			#[allow(unused_mut)]
-			let mut output = self.output.lock();
-			#[cfg(not(feature = "parking_lot"))]
-			let mut output = output.unwrap_or_else(|e| e.into_inner());
-			output.write_all(buf.as_bytes()).expect("io error");
-		})
+/// Write each of a record's key-value pairs on its own line.
+#[cfg(feature = "kv")]
+fn push_key_values<S: StringLike + fmt::Write>(output: &mut S, record: &log::Record) {
+	// only the source can fail, since the visitor doesn't
+	if record
+		.key_values()
+		.visit(&mut KvVisitor(&mut *output))
+		.is_err()
+	{
+		output.push('\n');
+		output.push_str(FMT_ERROR);
+	}
+}
+
+/// Writes each key-value pair on its own line, like `tracing` fields.
+#[cfg(feature = "kv")]
+struct KvVisitor<T>(T);
+
+#[cfg(feature = "kv")]
+impl<'kvs, T: StringLike + fmt::Write> VisitSource<'kvs> for KvVisitor<T> {
+	fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+		self.0.push('\n');
+		self.0.push_str(key.as_str());
+		self.0.push_str(": ");
+		match value.to_borrowed_str() {
+			Some(str) => write_args(&mut self.0, format_args!("{str:?}")),
+			None => write_args(&mut self.0, format_args!("{value}")),
+		}
+		Ok(())
+	}
+}
+
+/// Collects each key-value pair, for a [`channel_sink`](crate::channel_sink).
+#[cfg(feature = "kv")]
+struct KvCollector<'a>(&'a mut Vec<(String, String)>);
+
+#[cfg(feature = "kv")]
+impl<'kvs> VisitSource<'kvs> for KvCollector<'_> {
+	fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+		self.0.push((
+			key.as_str().to_owned(),
+			format_lossy(format_args!("{value}")),
+		));
+		Ok(())
 	}
 }
@@ -0,0 +1,203 @@
+use crate::Logger;
+use std::{
+	io::{self, Write},
+	net::{SocketAddr, TcpStream, ToSocketAddrs},
+	time::{Duration, Instant},
+};
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::PathBuf};
+
+const DEFAULT_MAX_PENDING: usize = 64 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A writer that sends logs to a TCP or Unix socket.
+///
+/// Connecting happens lazily, and the connection is re-established if it drops. While disconnected,
+/// writes are buffered (up to [`with_max_pending`](Self::with_max_pending) bytes), and anything
+/// beyond that is dropped. Writes never fail.
+///
+/// Everything happens on the thread that logs, while the [`Logger`]'s output is locked. So while
+/// the other end is down, a record can wait up to a second for a connection attempt (at most once
+/// a second), and a slow reader holds up every thread that logs until the socket takes the data.
+#[derive(Debug)]
+pub struct NetworkWriter {
+	address: Address,
+	stream: Option<Stream>,
+	pending: Vec<u8>,
+	max_pending: usize,
+	last_attempt: Option<Instant>,
+}
+
+#[derive(Debug)]
+enum Address {
+	Tcp(Vec<SocketAddr>),
+	#[cfg(unix)]
+	Unix(PathBuf),
+}
+
+#[derive(Debug)]
+enum Stream {
+	Tcp(TcpStream),
+	#[cfg(unix)]
+	Unix(UnixStream),
+}
+
+impl Stream {
+	fn as_write(&mut self) -> &mut dyn Write {
+		match self {
+			Stream::Tcp(stream) => stream,
+			#[cfg(unix)]
+			Stream::Unix(stream) => stream,
+		}
+	}
+}
+
+impl NetworkWriter {
+	/// Send logs to a TCP endpoint.
+	///
+	/// The address is resolved immediately, but the connection is made on the first write.
+	pub fn tcp<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+		let addresses: Vec<_> = address.to_socket_addrs()?.collect();
+		if addresses.is_empty() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"address resolved to nothing",
+			));
+		}
+		Ok(Self::with_address(Address::Tcp(addresses)))
+	}
+
+	/// Send logs to a Unix socket.
+	///
+	/// The connection is made on the first write.
+	#[cfg(unix)]
+	pub fn unix<P: Into<PathBuf>>(path: P) -> Self {
+		Self::with_address(Address::Unix(path.into()))
+	}
+
+	/// Set how many bytes may be buffered while disconnected. Defaults to 64 KiB.
+	pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+		self.max_pending = max_pending;
+		self
+	}
+
+	fn with_address(address: Address) -> Self {
+		Self {
+			address,
+			stream: None,
+			pending: Vec::new(),
+			max_pending: DEFAULT_MAX_PENDING,
+			last_attempt: None,
+		}
+	}
+
+	fn connect(&mut self) {
+		if self.stream.is_none() {
+			// don't stall every single log while the other end is down
+			let now = Instant::now();
+			if self
+				.last_attempt
+				.is_some_and(|last| now.duration_since(last) < RETRY_INTERVAL)
+			{
+				return;
+			}
+			self.last_attempt = Some(now);
+
+			self.stream = match &self.address {
+				Address::Tcp(addresses) => addresses
+					.iter()
+					.find_map(|address| TcpStream::connect_timeout(address, CONNECT_TIMEOUT).ok())
+					.map(Stream::Tcp),
+				#[cfg(unix)]
+				Address::Unix(path) => UnixStream::connect(path).ok().map(Stream::Unix),
+			};
+		}
+	}
+
+	/// Try to send `buf`, returning how much was sent. Disconnects on failure.
+	fn send(&mut self, buf: &[u8]) -> usize {
+		let mut sent = 0;
+		while sent < buf.len() {
+			self.connect();
+			let Some(stream) = &mut self.stream else {
+				break;
+			};
+			match stream.as_write().write(&buf[sent..]) {
+				Ok(0) => self.stream = None,
+				Ok(written) => sent += written,
+				Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+				Err(_) => self.stream = None,
+			}
+		}
+		sent
+	}
+
+	/// Try to send everything that's pending.
+	fn send_pending(&mut self) {
+		let pending = std::mem::take(&mut self.pending);
+		let sent = self.send(&pending);
+		self.pending = pending;
+		self.pending.drain(..sent);
+	}
+}
+
+impl Write for NetworkWriter {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.send_pending();
+		let mut rest = buf;
+		if self.pending.is_empty() {
+			// nothing has to go first, so this can be sent without buffering it
+			rest = &buf[self.send(buf)..];
+		}
+		if self.pending.len() + rest.len() <= self.max_pending {
+			self.pending.extend_from_slice(rest);
+		}
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.send_pending();
+		if let Some(stream) = &mut self.stream {
+			if stream.as_write().flush().is_err() {
+				self.stream = None;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl Logger<NetworkWriter> {
+	/// Create a new [`Logger`] that writes to a [`NetworkWriter`], with color disabled.
+	///
+	/// Connecting and sending block the thread that logs, while holding the output lock. See
+	/// [`NetworkWriter`] for how long that can take.
+	pub fn network(output: NetworkWriter) -> Self {
+		let mut logger = Self::new(output);
+		logger.color = false;
+		logger
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::TcpListener;
+
+	#[test]
+	fn max_pending_bounds_every_write() {
+		// nothing listens here anymore, so the writer stays disconnected
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let address = listener.local_addr().unwrap();
+		drop(listener);
+
+		let mut writer = NetworkWriter::tcp(address).unwrap().with_max_pending(4);
+		writer.write_all(b"too long").unwrap();
+		assert!(writer.pending.is_empty());
+		writer.write_all(b"fits").unwrap();
+		assert_eq!(writer.pending, b"fits");
+		writer.write_all(b"!").unwrap();
+		assert_eq!(writer.pending, b"fits");
+	}
+}
@@ -0,0 +1,51 @@
+use std::{
+	env,
+	io::{self, IsTerminal, Write},
+	process::{Command, Stdio},
+};
+
+#[cfg(test)]
+use std::cell::Cell;
+
+#[cfg(test)]
+thread_local! {
+	/// Lets tests pretend stdout is a terminal.
+	pub(super) static FORCE_TERMINAL: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether stdout is a terminal, so there's somewhere to show a pager.
+pub(super) fn stdout_is_terminal() -> bool {
+	#[cfg(test)]
+	if FORCE_TERMINAL.get() {
+		return true;
+	}
+	io::stdout().is_terminal()
+}
+
+/// Show `content` in `$PAGER` (or `less -R`). Returns whether that worked.
+pub(super) fn page(content: &str) -> bool {
+	let pager = env::var("PAGER").unwrap_or_default();
+	let mut args = pager.split_whitespace();
+	let mut command = match args.next() {
+		Some(program) => {
+			let mut command = Command::new(program);
+			command.args(args);
+			command
+		},
+		None => {
+			let mut command = Command::new("less");
+			command.arg("-R");
+			command
+		},
+	};
+
+	let Ok(mut child) = command.stdin(Stdio::piped()).spawn() else {
+		return false;
+	};
+	let written = child
+		.stdin
+		.take()
+		.is_some_and(|mut stdin| stdin.write_all(content.as_bytes()).is_ok());
+	// the user quitting early is fine, as long as the pager started
+	child.wait().is_ok() && written
+}
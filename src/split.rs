@@ -0,0 +1,142 @@
+use crate::{Level, Logger};
+use std::io;
+
+/// Writes records at or above a level with one [`Logger`], and everything else with another, e.g.
+/// warnings and errors to stderr, and the rest to stdout.
+///
+/// Each [`Logger`] keeps its own settings. To configure one and copy it to the other, use
+/// [`Logger::clone_with_output`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct SplitLogger<O = io::Stdout, E = io::Stderr>
+where
+	O: io::Write + Send + Sync + 'static,
+	E: io::Write + Send + Sync + 'static,
+{
+	/// Writes records below [`threshold`](Self::threshold).
+	pub out: Logger<O>,
+
+	/// Writes records at or above [`threshold`](Self::threshold).
+	pub err: Logger<E>,
+
+	/// Defaults to [`Level::Warn`].
+	pub threshold: Level,
+}
+
+impl<O, E> SplitLogger<O, E>
+where
+	O: io::Write + Send + Sync + 'static,
+	E: io::Write + Send + Sync + 'static,
+{
+	/// Create a [`SplitLogger`] from two loggers.
+	pub fn new(out: Logger<O>, err: Logger<E>) -> Self {
+		Self {
+			out,
+			err,
+			threshold: Level::Warn,
+		}
+	}
+
+	fn is_err(&self, level: Level) -> bool {
+		level >= self.threshold
+	}
+}
+
+impl Default for SplitLogger<io::Stdout, io::Stderr> {
+	/// Writes to stdout and stderr.
+	///
+	/// Both use the same settings. Note that `detect-color` only checks stdout, see
+	/// [`Logger::color`].
+	fn default() -> Self {
+		let out = Logger::default();
+		let err = out.clone_with_output(io::stderr());
+		Self::new(out, err)
+	}
+}
+
+#[cfg(feature = "log")]
+impl<O, E> log::Log for SplitLogger<O, E>
+where
+	O: io::Write + Send + Sync + 'static,
+	E: io::Write + Send + Sync + 'static,
+{
+	fn enabled(&self, metadata: &log::Metadata) -> bool {
+		if self.is_err(metadata.level().into()) {
+			self.err.enabled(metadata)
+		} else {
+			self.out.enabled(metadata)
+		}
+	}
+
+	fn flush(&self) {
+		self.out.flush();
+		self.err.flush();
+	}
+
+	fn log(&self, record: &log::Record) {
+		if self.is_err(record.level().into()) {
+			self.err.log(record);
+		} else {
+			self.out.log(record);
+		}
+	}
+}
+
+#[cfg(feature = "tracing")]
+mod tracing_impl {
+	use super::SplitLogger;
+	use std::io;
+	use tracing::{
+		span::{Attributes, Record},
+		Event, Id, Subscriber,
+	};
+	use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+	// both loggers keep track of every span, since either might write an event inside of it
+	impl<S, O, E> Layer<S> for SplitLogger<O, E>
+	where
+		S: Subscriber + for<'any> LookupSpan<'any>,
+		O: io::Write + Send + Sync + 'static,
+		E: io::Write + Send + Sync + 'static,
+	{
+		fn on_new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
+			self.out.on_new_span(attrs, id, ctx.clone());
+			self.err.on_new_span(attrs, id, ctx);
+		}
+
+		fn on_record(&self, id: &Id, values: &Record, ctx: Context<S>) {
+			self.out.on_record(id, values, ctx.clone());
+			self.err.on_record(id, values, ctx);
+		}
+
+		fn on_enter(&self, id: &Id, ctx: Context<S>) {
+			self.out.on_enter(id, ctx.clone());
+			self.err.on_enter(id, ctx);
+		}
+
+		fn on_exit(&self, id: &Id, ctx: Context<S>) {
+			self.out.on_exit(id, ctx.clone());
+			self.err.on_exit(id, ctx);
+		}
+
+		// only one of them should log the span's timing, see `span_timings`
+		fn on_close(&self, id: Id, ctx: Context<S>) {
+			let is_err = ctx
+				.metadata(&id)
+				.is_some_and(|metadata| self.is_err((*metadata.level()).into()));
+			if is_err {
+				self.err.on_close(id, ctx);
+			} else {
+				self.out.on_close(id, ctx);
+			}
+		}
+
+		fn on_event(&self, event: &Event, ctx: Context<S>) {
+			if self.is_err((*event.metadata().level()).into()) {
+				self.err.on_event(event, ctx);
+			} else {
+				self.out.on_event(event, ctx);
+			}
+		}
+	}
+}
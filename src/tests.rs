@@ -0,0 +1,1998 @@
+use crate::{
+	compat::Metadata,
+	util::{display_width, push_message},
+	Color, ColorDepth, Framing, IconSet, Level, LevelFilter, LevelStyle, LineFormat, LogRecord,
+	Logger, PathDepth, SplitLogger, Theme,
+};
+use std::{
+	backtrace::Backtrace,
+	fmt,
+	io::{self, Write},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		mpsc, Arc, Mutex,
+	},
+	thread,
+	time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "timestamps")]
+use crate::{DateDisplay, TimestampFormat};
+
+/// A writer that can still be read after it's given to a [`Logger`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+	pub fn contents(&self) -> String {
+		String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+	}
+
+	pub fn bytes(&self) -> Vec<u8> {
+		self.0.lock().unwrap().clone()
+	}
+}
+
+impl Write for Buffer {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.lock().unwrap().extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+/// A [`Logger`] that writes to a [`Buffer`], with everything that depends on the environment
+/// fixed: no color, the unicode icons, UTC, and no timestamps.
+pub(crate) fn test_logger() -> (Logger<Buffer>, Buffer) {
+	let buffer = Buffer::default();
+	let mut logger = Logger::new(buffer.clone());
+	logger.color = false;
+	logger.color_depth = ColorDepth::Basic;
+	logger.theme = Theme::basic();
+	logger.icons = IconSet::unicode();
+	#[cfg(feature = "timestamps")]
+	{
+		logger.timezone = time::UtcOffset::UTC;
+		logger.timestamp_from = LevelFilter::Off;
+	}
+	(logger, buffer)
+}
+
+/// Like [`test_logger`], but with color enabled.
+pub(crate) fn color_logger() -> (Logger<Buffer>, Buffer) {
+	let (mut logger, buffer) = test_logger();
+	logger.color = true;
+	(logger, buffer)
+}
+
+/// A record from `target`, made now.
+pub(crate) fn record(level: Level, target: &str, message: &str) -> LogRecord {
+	LogRecord {
+		level,
+		target: target.to_owned(),
+		file: None,
+		line: None,
+		time: SystemTime::now(),
+		message: message.to_owned(),
+		fields: Vec::new(),
+	}
+}
+
+pub(crate) fn log<T: io::Write + Send + Sync + 'static>(
+	logger: &Logger<T>,
+	level: Level,
+	target: &str,
+	message: &str,
+) {
+	logger.replay([record(level, target, message)]);
+}
+
+/// A time in UTC.
+#[cfg(feature = "timestamps")]
+pub(crate) fn at(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> SystemTime {
+	time::Date::from_calendar_date(year, month.try_into().unwrap(), day)
+		.unwrap()
+		.with_hms(hour, minute, second)
+		.unwrap()
+		.assume_utc()
+		.into()
+}
+
+/// Parse a duration written by [`push_duration`](crate::util::push_duration) or `Duration`'s
+/// `Debug` impl, like `12.3ms`.
+fn parse_duration(s: &str) -> Duration {
+	if s == "0" {
+		return Duration::ZERO;
+	}
+	let split = s.find(|c: char| c.is_alphabetic()).unwrap();
+	let (number, unit) = s.split_at(split);
+	let number: f64 = number.parse().unwrap();
+	let nanos = match unit {
+		"ns" => 1.0,
+		"µs" => 1e3,
+		"ms" => 1e6,
+		"s" => 1e9,
+		_ => panic!("unknown unit in {s:?}"),
+	};
+	Duration::from_nanos((number * nanos) as u64)
+}
+
+/// The SGR attributes that are turned on.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SgrState {
+	intensity: bool,
+	italic: bool,
+	underline: bool,
+	foreground: bool,
+	background: bool,
+}
+
+impl SgrState {
+	/// Apply the parameters of one `\x1b[...m` sequence.
+	fn apply(&mut self, params: &str) -> Result<(), String> {
+		let mut params = params.split(';');
+		while let Some(param) = params.next() {
+			match param {
+				"" | "0" => *self = Self::default(),
+				"1" | "2" => self.intensity = true,
+				"22" => self.intensity = false,
+				"3" => self.italic = true,
+				"23" => self.italic = false,
+				"4" => self.underline = true,
+				"24" => self.underline = false,
+				"39" => self.foreground = false,
+				"49" => self.background = false,
+				"38" | "48" => {
+					let count = match params.next() {
+						Some("5") => 1,
+						Some("2") => 3,
+						other => return Err(format!("bad extended color {other:?}")),
+					};
+					for _ in 0..count {
+						if params.next().and_then(|n| n.parse::<u8>().ok()).is_none() {
+							return Err(format!("bad extended color after {param}"));
+						}
+					}
+					if param == "38" {
+						self.foreground = true;
+					} else {
+						self.background = true;
+					}
+				},
+				_ => match param.parse::<u8>() {
+					Ok(30..=37 | 90..=97) => self.foreground = true,
+					Ok(40..=47 | 100..=107) => self.background = true,
+					_ => return Err(format!("unknown SGR parameter {param:?}")),
+				},
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Check that each line of `output` leaves the terminal the way it found it: every escape sequence
+/// ends on the line it started on, and everything it turned on is reset by the end of the line.
+pub(crate) fn check_ansi(output: &str) -> Result<(), String> {
+	for (number, line) in output.split('\n').enumerate() {
+		let mut state = SgrState::default();
+		let mut chars = line.chars();
+		while let Some(c) = chars.next() {
+			if c != '\x1b' {
+				continue;
+			}
+			match chars.next() {
+				Some('[') => {
+					let mut params = String::new();
+					let end = loop {
+						match chars.next() {
+							Some(c @ ('0'..='9' | ';')) => params.push(c),
+							other => break other,
+						}
+					};
+					match end {
+						Some('m') => state
+							.apply(&params)
+							.map_err(|e| format!("line {number}: {e}: {line:?}"))?,
+						Some(other) => {
+							return Err(format!("line {number}: unexpected {other:?}: {line:?}"))
+						},
+						None => return Err(format!("line {number}: unterminated CSI: {line:?}")),
+					}
+				},
+				// OSC, ended by BEL
+				Some(']') => {
+					if !chars.by_ref().any(|c| c == '\x07') {
+						return Err(format!("line {number}: unterminated OSC: {line:?}"));
+					}
+				},
+				other => {
+					return Err(format!(
+						"line {number}: stray escape before {other:?}: {line:?}"
+					))
+				},
+			}
+		}
+		if state != SgrState::default() {
+			return Err(format!("line {number}: not reset, {state:?}: {line:?}"));
+		}
+	}
+	Ok(())
+}
+
+#[track_caller]
+pub(crate) fn assert_ansi(output: &str) {
+	if let Err(e) = check_ansi(output) {
+		panic!("{e}");
+	}
+}
+
+#[test]
+fn check_ansi_accepts_balanced() {
+	assert_ansi("plain\ntext");
+	assert_ansi("\x1b[1;4mwarn\x1b[m rest");
+	assert_ansi("\x1b[38;2;1;2;3mx\x1b[39m \x1b[48;5;200my\x1b[0m");
+	assert_ansi("\x1b[2mdim\x1b[22m\x1b]9;notification\x07");
+}
+
+#[test]
+fn check_ansi_rejects_broken() {
+	// never reset
+	assert!(check_ansi("\x1b[31mred").is_err());
+	// only reset on the next line
+	assert!(check_ansi("\x1b[31mred\n\x1b[m").is_err());
+	// the intensity is reset, but not the color
+	assert!(check_ansi("\x1b[1;31mred\x1b[22m").is_err());
+	// unterminated
+	assert!(check_ansi("\x1b[31").is_err());
+	assert!(check_ansi("\x1b[3\nm").is_err());
+	assert!(check_ansi("\x1b]9;notification").is_err());
+	assert!(check_ansi("\x1b[38;5m").is_err());
+	assert!(check_ansi("trailing \x1b").is_err());
+}
+
+#[test]
+fn check_ansi_catches_broken_prefix() {
+	let (logger, buffer) = color_logger();
+	log(&logger, Level::Info, "app", "hello");
+	let output = buffer.contents();
+	assert_ansi(&output);
+	let reset = output.rfind("\x1b[m").unwrap();
+
+	// the prefix's final reset is dropped
+	let mut broken = output.clone();
+	broken.replace_range(reset..reset + 3, "");
+	assert!(check_ansi(&broken).is_err());
+
+	// the prefix stops in the middle of an escape sequence
+	let mut broken = output;
+	broken.truncate(reset + 2);
+	assert!(check_ansi(&broken).is_err());
+}
+
+#[test]
+fn prefix_is_reset_safe() {
+	for (depth, level_style, delta, line) in [
+		(ColorDepth::Basic, LevelStyle::Underline, false, None),
+		(ColorDepth::Ansi256, LevelStyle::Badge, true, Some(12)),
+		(ColorDepth::TrueColor, LevelStyle::Underline, true, Some(3)),
+		(ColorDepth::TrueColor, LevelStyle::Badge, false, None),
+	] {
+		let (mut logger, buffer) = color_logger();
+		logger.color_depth = depth;
+		logger.theme = Theme::for_depth(depth);
+		logger.level_style = level_style;
+		logger.show_delta = delta;
+		logger.target_color = Some(Arc::new(|target| {
+			(target == "app::db").then_some(Color::Rgb(10, 20, 200))
+		}));
+		#[cfg(feature = "timestamps")]
+		{
+			logger.timestamp_from = LevelFilter::Trace;
+		}
+		for level in Level::ALL {
+			let mut record = record(level, "app::db", "first\nsecond");
+			record.line = line;
+			logger.replay([record]);
+		}
+		logger.success("app", format_args!("done"));
+		logger.log_change(Level::Warn, "app", "retries", &1, &2);
+		assert_ansi(&buffer.contents());
+	}
+}
+
+#[test]
+fn aligns_custom_labels() {
+	let (mut logger, buffer) = test_logger();
+	logger.labels = ["t", "debug", "in", "warning", "e"];
+	logger.show_icon = false;
+	for level in Level::ALL {
+		log(&logger, level, "app", "message");
+	}
+	let output = buffer.contents();
+	let columns: Vec<_> = output
+		.lines()
+		.filter(|line| line.contains("app"))
+		.map(|line| line.find("app").unwrap())
+		.collect();
+	assert_eq!(columns.len(), 5);
+	assert!(
+		columns.iter().all(|&column| column == columns[0]),
+		"{output}"
+	);
+}
+
+#[test]
+fn aligns_default_labels_and_icons() {
+	let (logger, buffer) = test_logger();
+	for level in Level::ALL {
+		log(&logger, level, "app", "message");
+	}
+	let output = buffer.contents();
+	let columns: Vec<_> = output
+		.lines()
+		.filter_map(|line| line.find("app").map(|end| display_width(&line[..end])))
+		.collect();
+	assert_eq!(columns.len(), 5);
+	assert!(
+		columns.iter().all(|&column| column == columns[0]),
+		"{output}"
+	);
+}
+
+#[test]
+fn pads_wide_icons() {
+	let (mut logger, buffer) = test_logger();
+	logger.labels = ["TRC", "DBG", "INF", "WRN", "ERR"];
+	logger.icons[Level::Error] = "🔥";
+	for level in Level::ALL {
+		log(&logger, level, "app", "message");
+	}
+	let output = buffer.contents();
+	let columns: Vec<_> = output
+		.lines()
+		.step_by(2)
+		.zip(logger.labels)
+		.map(|(line, label)| display_width(&line[..line.find(label).unwrap()]))
+		.collect();
+	assert!(
+		columns.iter().all(|&column| column == columns[0]),
+		"{output}"
+	);
+}
+
+#[test]
+fn write_line_is_verbatim() {
+	let (logger, buffer) = test_logger();
+	logger.write_line("raw \x1b[1mline\x1b[m");
+	assert_eq!(buffer.contents(), "raw \x1b[1mline\x1b[m\n");
+}
+
+#[test]
+fn highlight_escalation() {
+	let (mut logger, buffer) = test_logger();
+	logger.highlight_escalation = true;
+	log(&logger, Level::Info, "app", "one");
+	log(&logger, Level::Info, "app", "two");
+	log(&logger, Level::Error, "app", "three");
+	log(&logger, Level::Info, "app", "four");
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().collect();
+	let rules: Vec<_> = (0..lines.len())
+		.filter(|&i| lines[i].starts_with('─'))
+		.collect();
+	assert_eq!(rules.len(), 1, "{output}");
+	assert!(lines[rules[0] + 1].contains("error"), "{output}");
+}
+
+#[test]
+fn null_framing() {
+	let (mut logger, buffer) = test_logger();
+	logger.frame = Framing::Null;
+	log(&logger, Level::Info, "app", "one");
+	log(&logger, Level::Info, "app", "two");
+	let output = buffer.contents();
+	let records: Vec<_> = output.split_terminator('\0').collect();
+	assert_eq!(records.len(), 2);
+	assert!(records[0].ends_with("one") && records[1].ends_with("two"));
+}
+
+#[test]
+fn length_prefixed_framing() {
+	let (mut logger, buffer) = test_logger();
+	logger.frame = Framing::LengthPrefixed;
+	log(&logger, Level::Info, "app", "one");
+	log(&logger, Level::Warn, "app", "second");
+	let bytes = buffer.bytes();
+	let mut rest = &bytes[..];
+	let mut records = Vec::new();
+	while !rest.is_empty() {
+		let (len, tail) = rest.split_at(4);
+		let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+		records.push(std::str::from_utf8(&tail[..len]).unwrap().to_owned());
+		rest = &tail[len..];
+	}
+	assert_eq!(records.len(), 2);
+	assert!(records[0].ends_with("one") && records[1].ends_with("second"));
+
+	// the length and the record go out together, so a writer can't drop one without the other
+	let counting = Counting::default();
+	let mut logger = Logger::new(counting.clone());
+	logger.frame = Framing::LengthPrefixed;
+	log(&logger, Level::Info, "app", "one");
+	assert_eq!(counting.writes.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn target_color() {
+	let (mut logger, buffer) = color_logger();
+	logger.target_color = Some(Arc::new(|target| {
+		target.starts_with("db").then_some(Color::Blue)
+	}));
+	log(&logger, Level::Info, "db", "query");
+	log(&logger, Level::Info, "http", "request");
+	let output = buffer.contents();
+	assert!(output.contains("\x1b[34mdb"), "{output:?}");
+	assert!(!output.contains("\x1b[34mhttp"), "{output:?}");
+	assert_ansi(&output);
+}
+
+#[test]
+fn leaf_path_depth() {
+	let (mut logger, buffer) = test_logger();
+	logger.path_depth = PathDepth::LeafOnly;
+	log(&logger, Level::Info, "myapp::storage::db", "query");
+	let output = buffer.contents();
+	assert!(output.contains(" db"), "{output}");
+	assert!(!output.contains("storage"), "{output}");
+}
+
+#[test]
+fn skips_empty_path_segments() {
+	let (logger, buffer) = test_logger();
+	log(&logger, Level::Info, "a::b::", "message");
+	let output = buffer.contents();
+	assert!(output.lines().next().unwrap().ends_with(" a/b"), "{output}");
+}
+
+#[test]
+fn module_fallback() {
+	let meta = Metadata {
+		level: Level::Info,
+		target: "bridge",
+		module_path: None,
+		file: None,
+		line: None,
+	};
+	let (mut logger, buffer) = test_logger();
+	logger.write_record(&meta, None, |output| {
+		push_message(output, format_args!("one"))
+	});
+	logger.module_fallback = Some("external");
+	logger.write_record(&meta, None, |output| {
+		push_message(output, format_args!("two"))
+	});
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().collect();
+	assert!(lines[0].ends_with(" bridge"), "{output}");
+	assert!(lines[2].ends_with(" external"), "{output}");
+}
+
+#[test]
+fn timed() {
+	let (logger, buffer) = test_logger();
+	let result: Result<(), ()> = logger.timed(Level::Info, "app", "sleep", || {
+		thread::sleep(Duration::from_millis(20));
+		Ok(())
+	});
+	assert!(result.is_ok());
+	let _ = logger.timed(Level::Warn, "app", "fail", || Err::<(), _>("nope"));
+	let output = buffer.contents();
+	let elapsed = output
+		.lines()
+		.find_map(|line| line.trim().strip_prefix("sleep completed in "))
+		.unwrap();
+	assert!(
+		parse_duration(elapsed) >= Duration::from_millis(20),
+		"{output}"
+	);
+	assert!(output.contains("fail failed after "), "{output}");
+}
+
+#[test]
+fn bracketed_icons() {
+	let (mut logger, buffer) = test_logger();
+	logger.icons = IconSet::bracketed();
+	log(&logger, Level::Error, "app", "broken");
+	assert!(buffer.contents().contains("[E] error"));
+}
+
+#[test]
+fn nerd_font_icons() {
+	let (mut logger, buffer) = test_logger();
+	logger.icons = IconSet::nerd_font();
+	log(&logger, Level::Warn, "app", "careful");
+	log(&logger, Level::Error, "app", "broken");
+	let output = buffer.contents();
+	assert!(output.contains('\u{f071}') && output.contains('\u{f057}'));
+}
+
+#[test]
+fn custom_icon() {
+	let (mut logger, buffer) = test_logger();
+	logger.icons[Level::Warn] = "!!";
+	log(&logger, Level::Warn, "app", "careful");
+	assert!(buffer.contents().contains("!! warn"));
+}
+
+#[test]
+fn hides_icons() {
+	let (mut logger, buffer) = test_logger();
+	logger.show_icon = false;
+	log(&logger, Level::Error, "app", "broken");
+	let output = buffer.contents();
+	assert!(!output.contains(IconSet::unicode().error), "{output}");
+	assert!(output.contains("error app") && output.contains("broken"));
+}
+
+#[test]
+fn custom_labels() {
+	let (mut logger, buffer) = test_logger();
+	logger.labels = ["TRC", "DBG", "INF", "WRN", "ERR"];
+	for level in Level::ALL {
+		log(&logger, level, "app", "message");
+	}
+	let output = buffer.contents();
+	for label in logger.labels {
+		assert!(output.contains(&format!("{label} app")), "{output}");
+	}
+}
+
+#[test]
+#[cfg(feature = "icons-ascii")]
+fn ascii_icons_by_default() {
+	assert_eq!(IconSet::default(), IconSet::ascii());
+}
+
+#[test]
+#[cfg(not(feature = "icons-ascii"))]
+fn unicode_icons_by_default() {
+	assert_eq!(IconSet::default(), IconSet::unicode());
+}
+
+#[test]
+fn show_delta() {
+	let (mut logger, buffer) = test_logger();
+	logger.show_delta = true;
+	log(&logger, Level::Info, "app", "one");
+	thread::sleep(Duration::from_millis(20));
+	log(&logger, Level::Info, "app", "two");
+	let output = buffer.contents();
+	let deltas: Vec<_> = output
+		.lines()
+		.filter_map(|line| {
+			let (_, rest) = line.split_once(" +")?;
+			rest.split(' ').next().map(parse_duration)
+		})
+		.collect();
+	assert_eq!(deltas.len(), 2, "{output}");
+	assert_eq!(deltas[0], Duration::ZERO);
+	assert!(deltas[1] >= Duration::from_millis(20), "{output}");
+}
+
+#[test]
+fn padded_line_numbers() {
+	let (mut logger, buffer) = test_logger();
+	logger.line_format = LineFormat {
+		file: false,
+		width: 4,
+	};
+	let mut short = record(Level::Info, "app", "message");
+	short.line = Some(7);
+	let mut long = short.clone();
+	long.line = Some(123_456);
+	logger.replay([short, long]);
+	let output = buffer.contents();
+	assert!(output.contains("app:0007\n"), "{output}");
+	assert!(output.contains("app:123456\n"), "{output}");
+}
+
+#[test]
+fn file_locations() {
+	let (mut logger, buffer) = test_logger();
+	logger.line_format.file = true;
+	let mut record = record(Level::Info, "app::db", "message");
+	record.file = Some("src/db.rs".to_owned());
+	record.line = Some(12);
+	logger.replay([record]);
+	assert!(buffer.contents().contains(" src/db.rs:12\n"));
+}
+
+#[test]
+fn theme_for_each_depth() {
+	for (depth, escape) in [
+		(ColorDepth::Basic, "\x1b[9"),
+		(ColorDepth::Ansi256, "\x1b[38;5;"),
+		(ColorDepth::TrueColor, "\x1b[38;2;"),
+	] {
+		let (mut logger, buffer) = color_logger();
+		logger.level_style = LevelStyle::Underline;
+		logger.color_depth = depth;
+		logger.theme = Theme::for_depth(depth);
+		log(&logger, Level::Info, "app", "message");
+		let output = buffer.contents();
+		assert!(output.contains(escape), "{depth:?}: {output:?}");
+		assert_ansi(&output);
+	}
+}
+
+#[test]
+fn downgrades_theme() {
+	let (mut logger, buffer) = color_logger();
+	logger.color_depth = ColorDepth::Basic;
+	logger.theme = Theme::truecolor();
+	for level in Level::ALL {
+		log(&logger, level, "app", "message");
+	}
+	let output = buffer.contents();
+	assert!(
+		!output.contains("38;") && !output.contains("48;"),
+		"{output:?}"
+	);
+	assert_ansi(&output);
+}
+
+#[test]
+fn custom_theme_color() {
+	let (mut logger, buffer) = color_logger();
+	logger.color_depth = ColorDepth::TrueColor;
+	logger.theme[Level::Error] = Color::Rgb(1, 2, 3);
+	log(&logger, Level::Error, "app", "broken");
+	let output = buffer.contents();
+	assert!(output.contains("38;2;1;2;3"), "{output:?}");
+	assert_ansi(&output);
+}
+
+#[test]
+fn badge() {
+	let (mut logger, buffer) = color_logger();
+	logger.level_style = LevelStyle::Badge;
+	log(&logger, Level::Error, "app", "broken");
+	let output = buffer.contents();
+	// bright red background, light text
+	assert!(output.contains("\x1b[101;97m error "), "{output:?}");
+	assert_ansi(&output);
+}
+
+#[test]
+fn transform() {
+	let (mut logger, buffer) = test_logger();
+	logger.transform = Some(Arc::new(|record| *record = record.to_uppercase()));
+	log(&logger, Level::Info, "app", "quiet");
+	let output = buffer.contents();
+	assert!(
+		output.contains("INFO APP") && output.contains("QUIET"),
+		"{output}"
+	);
+}
+
+#[test]
+fn flush_on() {
+	let buffer = Buffer::default();
+	let mut logger = Logger::new(io::BufWriter::new(buffer.clone()));
+	logger.color = false;
+	logger.flush_on = LevelFilter::Error;
+	log(&logger, Level::Debug, "app", "buffered");
+	assert_eq!(buffer.contents(), "");
+	log(&logger, Level::Error, "app", "flushed");
+	let output = buffer.contents();
+	assert!(output.contains("buffered") && output.contains("flushed"));
+}
+
+/// Counts writes and flushes.
+#[derive(Debug, Clone, Default)]
+struct Counting {
+	writes: Arc<AtomicUsize>,
+	flushes: Arc<AtomicUsize>,
+}
+
+impl Write for Counting {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.writes.fetch_add(1, Ordering::Relaxed);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.flushes.fetch_add(1, Ordering::Relaxed);
+		Ok(())
+	}
+}
+
+#[test]
+fn buffered() {
+	let counting = Counting::default();
+	let logger = Logger::buffered(counting.clone());
+	log(&logger, Level::Info, "app", "one");
+	log(&logger, Level::Info, "app", "two");
+	assert_eq!(counting.writes.load(Ordering::Relaxed), 0);
+	log(&logger, Level::Warn, "app", "three");
+	assert_eq!(counting.writes.load(Ordering::Relaxed), 1);
+	assert_eq!(counting.flushes.load(Ordering::Relaxed), 1);
+	log(&logger, Level::Info, "app", "four");
+	drop(logger);
+	assert_eq!(counting.writes.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn write_line_flushes() {
+	let counting = Counting::default();
+	let mut logger = Logger::new(counting.clone());
+	logger.write_line("raw");
+	assert_eq!(counting.flushes.load(Ordering::Relaxed), 0);
+	logger.flush_on = LevelFilter::Error;
+	logger.write_line("raw");
+	assert_eq!(counting.flushes.load(Ordering::Relaxed), 1);
+
+	let logger = Logger::buffered(counting.clone());
+	logger.write_line("raw");
+	assert_eq!(counting.writes.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn auto_line_buffer() {
+	for (enabled, terminal, flushed) in [
+		(true, true, true),
+		(true, false, false),
+		(false, true, false),
+	] {
+		let counting = Counting::default();
+		let mut logger = Logger::buffered(counting.clone());
+		logger.auto_line_buffer_with(enabled, |_| terminal);
+		log(&logger, Level::Debug, "app", "one");
+		log(&logger, Level::Debug, "app", "two");
+		let writes = counting.writes.load(Ordering::Relaxed);
+		assert_eq!(writes, if flushed { 2 } else { 0 }, "{enabled} {terminal}");
+	}
+}
+
+#[test]
+#[cfg(unix)]
+fn auto_line_buffer_detects_files() {
+	let file = std::fs::OpenOptions::new()
+		.write(true)
+		.open("/dev/null")
+		.unwrap();
+	let mut logger = Logger::buffered(file);
+	logger.auto_line_buffer(true);
+	assert_eq!(logger.flush_on, LevelFilter::Off);
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn show_timezone() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.show_timezone = true;
+	logger.set_timezone_str("+05:30").unwrap();
+	let mut record = record(Level::Info, "app", "message");
+	record.time = at(2026, 1, 2, 3, 4, 5);
+	logger.replay([record]);
+	assert!(buffer.contents().contains("8:34:05-AM-2026/01/02 +05:30\n"));
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn timestamps() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	let mut record = record(Level::Info, "app", "message");
+	record.time = at(2026, 3, 7, 14, 5, 9);
+	logger.replay([record.clone()]);
+	logger.time_24h = true;
+	logger.replay([record.clone()]);
+	logger.date_separator = '-';
+	logger.date_time_separator = ' ';
+	logger.replay([record.clone()]);
+	logger.date_display = DateDisplay::MonthDay;
+	logger.replay([record.clone()]);
+	logger.date_display = DateDisplay::None;
+	logger.replay([record]);
+	let output = buffer.contents();
+	let times: Vec<_> = output
+		.lines()
+		.step_by(2)
+		.map(|line| line.split_once("app ").unwrap().1)
+		.collect();
+	assert_eq!(
+		times,
+		[
+			"2:05:09-PM-2026/03/07",
+			"14:05:09-2026/03/07",
+			"14:05:09 2026-03-07",
+			"14:05:09 03-07",
+			"14:05:09",
+		]
+	);
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn midnight_and_noon() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.date_display = DateDisplay::None;
+	let mut record = record(Level::Info, "app", "message");
+	for hour in [0, 12] {
+		record.time = at(2026, 1, 2, hour, 15, 0);
+		logger.replay([record.clone()]);
+	}
+	// the same times, in the logger's timezone
+	logger.set_timezone_str("+05:30").unwrap();
+	for hour in [18, 6] {
+		record.time = at(2026, 1, 2, hour, 45, 0);
+		logger.replay([record.clone()]);
+	}
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().step_by(2).collect();
+	for pair in lines.chunks(2) {
+		assert!(pair[0].ends_with(" 12:15:00-AM"), "{output}");
+		assert!(pair[1].ends_with(" 12:15:00-PM"), "{output}");
+	}
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn zero_pads_dates() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.time_24h = true;
+	let mut record = record(Level::Info, "app", "message");
+	record.time = at(2026, 1, 2, 3, 4, 5);
+	logger.replay([record]);
+	assert!(buffer.contents().contains(" 03:04:05-2026/01/02\n"));
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn timestamp_from() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Warn;
+	let mut debug = record(Level::Debug, "app", "message");
+	debug.time = at(2026, 1, 2, 3, 4, 5);
+	let mut warn = debug.clone();
+	warn.level = Level::Warn;
+	logger.replay([debug, warn]);
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().collect();
+	assert!(lines[0].ends_with("debug app"), "{output}");
+	assert!(
+		lines[2].ends_with("warn app 3:04:05-AM-2026/01/02"),
+		"{output}"
+	);
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn collapse_timestamps() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.collapse_timestamps = true;
+	let mut record = record(Level::Info, "app", "message");
+	record.time = at(2026, 1, 2, 3, 4, 5);
+	logger.replay([record.clone(), record.clone(), record]);
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().step_by(2).collect();
+	assert!(lines[0].ends_with("app 3:04:05-AM-2026/01/02"), "{output}");
+	for line in &lines[1..] {
+		assert_eq!(line.trim_end(), lines[0].split(" 3:").next().unwrap());
+		assert_eq!(line.len(), lines[0].len());
+	}
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn collapse_dates() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.collapse_dates = true;
+	let mut record = record(Level::Info, "app", "message");
+	let times = [
+		at(2026, 1, 2, 3, 4, 5),
+		at(2026, 1, 2, 9, 0, 0),
+		at(2026, 1, 3, 0, 0, 1),
+	];
+	for time in times {
+		record.time = time;
+		logger.replay([record.clone()]);
+	}
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().step_by(2).collect();
+	assert!(lines[0].ends_with("3:04:05-AM-2026/01/02"), "{output}");
+	assert!(lines[1].ends_with("9:00:00-AM"), "{output}");
+	assert!(lines[2].ends_with("12:00:01-AM-2026/01/03"), "{output}");
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn rfc3339_round_trips() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.timestamp_format = TimestampFormat::Rfc3339;
+	let time = at(2026, 3, 7, 14, 5, 9);
+	for offset in ["Z", "+05:30", "-08:00"] {
+		logger.set_timezone_str(offset).unwrap();
+		let mut record = record(Level::Info, "app", "message");
+		record.time = time;
+		logger.replay([record]);
+	}
+	let output = buffer.contents();
+	let timestamps: Vec<_> = output
+		.lines()
+		.step_by(2)
+		.map(|line| line.rsplit_once(' ').unwrap().1)
+		.collect();
+	assert_eq!(
+		timestamps,
+		[
+			"2026-03-07T14:05:09Z",
+			"2026-03-07T19:35:09+05:30",
+			"2026-03-07T06:05:09-08:00",
+		]
+	);
+	// time's `parsing` feature would pull in `time-macros`, so parse them by hand
+	for timestamp in timestamps {
+		let (date_time, offset) = timestamp.split_at(19);
+		let number = |start: usize| date_time[start..start + 2].parse::<u8>().unwrap();
+		let parsed = time::Date::from_calendar_date(
+			date_time[..4].parse().unwrap(),
+			number(5).try_into().unwrap(),
+			number(8),
+		)
+		.unwrap()
+		.with_hms(number(11), number(14), number(17))
+		.unwrap()
+		.assume_offset(crate::timezone::parse_offset(offset).unwrap());
+		assert_eq!(SystemTime::from(parsed), time, "{timestamp}");
+	}
+}
+
+#[test]
+#[cfg(feature = "timestamps")]
+fn elapsed_timestamps() {
+	let (mut logger, buffer) = test_logger();
+	logger.timestamp_from = LevelFilter::Trace;
+	logger.timestamp_format = TimestampFormat::Elapsed;
+	let mut first = record(Level::Info, "app", "message");
+	first.time = at(2026, 1, 2, 3, 4, 5);
+	let mut second = first.clone();
+	second.time = first.time + Duration::from_millis(1500);
+	logger.replay([first, second]);
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().step_by(2).collect();
+	assert!(lines[0].ends_with("app [   0.000s]"), "{output}");
+	assert!(lines[1].ends_with("app [   1.500s]"), "{output}");
+}
+
+#[test]
+fn static_fields() {
+	let (logger, buffer) = test_logger();
+	let logger = logger.with_fields(&[("component", "auth")]);
+	log(&logger, Level::Info, "app", "one");
+	log(&logger, Level::Warn, "app", "two");
+	let output = buffer.contents();
+	assert_eq!(output.matches("component: \"auth\"").count(), 2, "{output}");
+}
+
+#[test]
+fn log_summary() {
+	let (logger, buffer) = test_logger();
+	log(&logger, Level::Error, "app", "message");
+	log(&logger, Level::Warn, "app", "message");
+	log(&logger, Level::Warn, "app", "message");
+	log(&logger, Level::Info, "app", "message");
+	logger.log_summary();
+	let output = buffer.contents();
+	assert_eq!(
+		output.lines().last().unwrap(),
+		"summary: 1 error, 2 warnings, 1 info"
+	);
+
+	let (logger, buffer) = test_logger();
+	logger.log_summary();
+	assert_eq!(buffer.contents(), "summary: no records\n");
+}
+
+#[test]
+fn single_line() {
+	let (mut logger, buffer) = test_logger();
+	logger.single_line = true;
+	log(&logger, Level::Info, "app", "first\nsecond");
+	let output = buffer.contents();
+	assert_eq!(output.lines().count(), 1, "{output}");
+	assert!(output.contains("first\\nsecond"), "{output}");
+}
+
+#[test]
+fn throttle() {
+	let (mut logger, buffer) = test_logger();
+	logger.throttle(Level::Error, Duration::from_millis(100));
+	for _ in 0..5 {
+		log(&logger, Level::Error, "app", "storm");
+	}
+	log(&logger, Level::Info, "app", "unaffected");
+	assert_eq!(buffer.contents().matches("storm").count(), 1);
+	thread::sleep(Duration::from_millis(120));
+	log(&logger, Level::Error, "app", "storm");
+	let output = buffer.contents();
+	assert_eq!(output.matches("storm").count(), 2, "{output}");
+	assert!(output.contains("unaffected"));
+	assert!(output.ends_with("(+4 suppressed)\n"), "{output}");
+}
+
+#[test]
+fn success() {
+	let (mut logger, buffer) = color_logger();
+	logger.level_style = LevelStyle::Underline;
+	logger.success("app", format_args!("done"));
+	let output = buffer.contents();
+	// bright green check, the info level
+	assert!(output.contains("\x1b[92m✔ "), "{output:?}");
+	assert!(output.contains(&format!("m{}\x1b", logger.labels[Level::Info as usize])));
+	assert!(output.contains("done"));
+	assert_ansi(&output);
+}
+
+#[test]
+fn suspend() {
+	let (mut logger, buffer) = test_logger();
+	let calls = Arc::new(Mutex::new(Vec::new()));
+	let inner = calls.clone();
+	let output = buffer.clone();
+	logger.set_suspend(move |write| {
+		inner.lock().unwrap().push(output.contents().len());
+		write();
+		inner.lock().unwrap().push(output.contents().len());
+	});
+	log(&logger, Level::Info, "app", "message");
+	let calls = calls.lock().unwrap();
+	assert_eq!(calls.len(), 2);
+	assert_eq!(calls[0], 0);
+	assert_eq!(calls[1], buffer.contents().len());
+}
+
+#[test]
+fn change() {
+	let (logger, buffer) = test_logger();
+	logger.log_change(Level::Info, "app", "retries", &1, &2);
+	assert!(buffer.contents().contains("retries: 1 → 2"));
+}
+
+#[test]
+fn prefix_content_separator() {
+	let (mut logger, buffer) = test_logger();
+	logger.prefix_content_separator = Some(" │ ");
+	log(&logger, Level::Info, "app", "short");
+	log(&logger, Level::Info, "app", "two\nlines");
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().collect();
+	assert!(lines[0].ends_with("app │ short"), "{output}");
+	assert!(lines[1].ends_with("app"), "{output}");
+}
+
+#[test]
+fn notify_on() {
+	let (mut logger, buffer) = color_logger();
+	logger.notify_on = LevelFilter::Error;
+	log(&logger, Level::Info, "app", "fine");
+	log(&logger, Level::Error, "app", "broken");
+	let output = buffer.contents();
+	assert_eq!(output.matches("\x1b]9;").count(), 1, "{output:?}");
+	let notification = output.split("\x1b]9;").nth(1).unwrap();
+	assert!(notification.contains("error app: broken\x07"), "{output:?}");
+	assert_ansi(&output);
+
+	// it would break the frames
+	let (mut logger, buffer) = color_logger();
+	logger.notify_on = LevelFilter::Error;
+	logger.frame = Framing::Null;
+	log(&logger, Level::Error, "app", "broken");
+	assert!(!buffer.contents().contains("\x1b]9;"));
+}
+
+#[test]
+fn format_backtrace() {
+	let (logger, _) = test_logger();
+	let formatted = logger.format_backtrace(&Backtrace::force_capture());
+	assert!(formatted.lines().count() > 1, "{formatted}");
+	for line in formatted.lines() {
+		let (number, function) = line.trim_start().split_once(": ").unwrap();
+		assert!(number.bytes().all(|b| b.is_ascii_digit()), "{line}");
+		assert!(!function.is_empty(), "{line}");
+	}
+	assert!(formatted.contains("format_backtrace"), "{formatted}");
+
+	assert_eq!(
+		logger.format_backtrace(&Backtrace::disabled()),
+		"disabled backtrace"
+	);
+
+	let (logger, _) = color_logger();
+	assert_ansi(&logger.format_backtrace(&Backtrace::force_capture()));
+}
+
+#[test]
+fn color_from_env() {
+	let _env = lock_env();
+	std::env::set_var("NO_COLOR", "1");
+	std::env::set_var("CLICOLOR_FORCE", "1");
+	assert!(!Logger::color_from_env());
+	std::env::set_var("NO_COLOR", "");
+	assert!(Logger::color_from_env());
+	std::env::set_var("CLICOLOR_FORCE", "0");
+	#[cfg(not(feature = "detect-color"))]
+	assert!(!Logger::color_from_env());
+	std::env::remove_var("NO_COLOR");
+	std::env::remove_var("CLICOLOR_FORCE");
+}
+
+#[test]
+fn rule() {
+	let _env = lock_env();
+	std::env::set_var("COLUMNS", "40");
+	let (logger, buffer) = test_logger();
+	logger.rule(None);
+	logger.rule(Some("build"));
+	logger.rule(Some("ビルド"));
+	std::env::remove_var("COLUMNS");
+
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().collect();
+	assert_eq!(lines.len(), 3, "{output}");
+	assert!(
+		lines[1].contains(" build ") && lines[2].contains(" ビルド "),
+		"{output}"
+	);
+	for line in lines {
+		assert_eq!(display_width(line), 40, "{line:?}");
+	}
+}
+
+/// Tests that change environment variables hold this, so they don't see each other's changes.
+pub(crate) fn lock_env() -> std::sync::MutexGuard<'static, ()> {
+	static ENV: Mutex<()> = Mutex::new(());
+	ENV.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[test]
+fn falls_back_when_detection_fails() {
+	let _env = lock_env();
+	let saved: Vec<_> = ["TERM", "TZ"]
+		.into_iter()
+		.map(|var| (var, std::env::var_os(var)))
+		.collect();
+	std::env::set_var("TERM", "not a terminal");
+	std::env::set_var("TZ", "Nowhere/Garbage");
+	for var in [
+		"COLORTERM",
+		"FORCE_COLOR",
+		"CLICOLOR_FORCE",
+		"IGNORE_IS_TERMINAL",
+	] {
+		std::env::remove_var(var);
+	}
+
+	let logger = Logger::new(io::sink());
+	assert!(!logger.color);
+	#[cfg(feature = "timestamps")]
+	assert_eq!(logger.timezone, time::UtcOffset::UTC);
+
+	for (var, value) in saved {
+		match value {
+			Some(value) => std::env::set_var(var, value),
+			None => std::env::remove_var(var),
+		}
+	}
+}
+
+#[test]
+#[cfg(unix)]
+fn page_long_content() {
+	let _env = lock_env();
+	crate::pager::FORCE_TERMINAL.set(true);
+	let paged = std::env::temp_dir().join(format!("tinylog-pager-{}", std::process::id()));
+	std::env::set_var("PAGER", format!("dd status=none of={}", paged.display()));
+
+	let (mut logger, buffer) = test_logger();
+	logger.page_long_content = true;
+	logger.page_threshold = Some(3);
+	log(&logger, Level::Info, "app", "short");
+	log(&logger, Level::Info, "app", "1\n2\n3\n4\n5");
+	let pager = std::fs::read_to_string(&paged).unwrap();
+	std::fs::remove_file(&paged).unwrap();
+	assert!(pager.contains("info app\n        1\n") && pager.ends_with("        5"));
+	let output = buffer.contents();
+	assert!(
+		output.contains("short") && !output.contains("        5"),
+		"{output}"
+	);
+
+	// written normally if the pager can't be started
+	std::env::set_var("PAGER", "tinylog-no-such-pager");
+	log(&logger, Level::Info, "app", "a\nb\nc\nd");
+	assert!(buffer.contents().contains("        d\n"));
+
+	std::env::remove_var("PAGER");
+	crate::pager::FORCE_TERMINAL.set(false);
+}
+
+#[test]
+fn route() {
+	let (mut logger, buffer) = test_logger();
+	let traces = Buffer::default();
+	logger.route(Level::Trace, traces.clone());
+	log(&logger, Level::Trace, "app", "detail");
+	log(&logger, Level::Info, "app", "overview");
+	let (traces, output) = (traces.contents(), buffer.contents());
+	assert!(traces.contains("detail") && !traces.contains("overview"));
+	assert!(output.contains("overview") && !output.contains("detail"));
+}
+
+/// A writer whose reader went away.
+struct BrokenPipe;
+
+impl Write for BrokenPipe {
+	fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+		Err(io::ErrorKind::BrokenPipe.into())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Err(io::ErrorKind::BrokenPipe.into())
+	}
+}
+
+#[test]
+fn on_error() {
+	let mut logger = Logger::new(BrokenPipe);
+	let errors = Arc::new(AtomicUsize::new(0));
+	let counter = errors.clone();
+	logger.on_error = Some(Arc::new(move |error| {
+		assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+		counter.fetch_add(1, Ordering::Relaxed);
+	}));
+	logger.flush_on = LevelFilter::Error;
+	log(&logger, Level::Info, "app", "lost");
+	assert_eq!(errors.load(Ordering::Relaxed), 1);
+	log(&logger, Level::Error, "app", "lost");
+	assert_eq!(errors.load(Ordering::Relaxed), 3);
+	logger.write_line("lost");
+	assert_eq!(errors.load(Ordering::Relaxed), 5);
+}
+
+/// A value that can't be formatted.
+struct Unformattable;
+
+impl fmt::Display for Unformattable {
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Err(fmt::Error)
+	}
+}
+
+impl fmt::Debug for Unformattable {
+	fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+		Err(fmt::Error)
+	}
+}
+
+#[test]
+fn fmt_errors() {
+	let (logger, buffer) = test_logger();
+	logger.success("app", format_args!("{Unformattable}"));
+	logger
+		.event(Level::Info, "app")
+		.field("value", Unformattable)
+		.message(Unformattable)
+		.emit();
+	let output = buffer.contents();
+	assert_eq!(output.matches("<fmt error>").count(), 3, "{output}");
+	assert!(output.contains("value: <fmt error>"), "{output}");
+
+	let (sink, receiver) = crate::channel_sink();
+	sink.event(Level::Info, "app")
+		.field("value", Unformattable)
+		.message(Unformattable)
+		.emit();
+	let record = receiver.try_recv().unwrap();
+	assert_eq!(record.message, "<fmt error>");
+	assert_eq!(
+		record.fields,
+		[("value".to_owned(), "<fmt error>".to_owned())]
+	);
+}
+
+#[test]
+#[cfg(feature = "log")]
+fn log_fmt_errors() {
+	use log::Log;
+
+	let (logger, buffer) = test_logger();
+	let (sink, receiver) = crate::channel_sink();
+	for logger in [&logger as &dyn Log, &sink] {
+		logger.log(
+			&log::Record::builder()
+				.level(log::Level::Info)
+				.args(format_args!("{Unformattable}"))
+				.build(),
+		);
+	}
+	assert!(buffer.contents().ends_with("\n        <fmt error>\n"));
+	assert_eq!(receiver.try_recv().unwrap().message, "<fmt error>");
+}
+
+#[test]
+#[cfg(feature = "kv")]
+fn kv_fmt_errors() {
+	use log::{kv::Value, Log};
+
+	let pairs = [("value", Value::from_display(&Unformattable))];
+	let (logger, buffer) = test_logger();
+	logger.log(
+		&log::Record::builder()
+			.level(log::Level::Info)
+			.args(format_args!("message"))
+			.key_values(&pairs)
+			.build(),
+	);
+	let output = buffer.contents();
+	assert!(output.contains("value: <fmt error>"), "{output}");
+}
+
+#[test]
+fn level() {
+	let (logger, buffer) = test_logger();
+	log(&logger, Level::Debug, "app", "first");
+	logger.set_level(LevelFilter::Info);
+	assert_eq!(logger.level(), LevelFilter::Info);
+	log(&logger, Level::Debug, "app", "second");
+	log(&logger, Level::Info, "app", "third");
+	let output = buffer.contents();
+	assert!(output.contains("first") && output.contains("third"));
+	assert!(!output.contains("second"), "{output}");
+}
+
+#[test]
+fn target_levels() {
+	let (mut logger, buffer) = test_logger();
+	logger.set_level(LevelFilter::Info);
+	logger.add_target_level("app::db", LevelFilter::Debug);
+	logger.add_target_level("app::net", LevelFilter::Warn);
+	log(&logger, Level::Debug, "app::db::pool", "query");
+	log(&logger, Level::Debug, "app::dbx", "dbx");
+	log(&logger, Level::Info, "app::net", "connected");
+	log(&logger, Level::Warn, "app::net", "dropped");
+	let output = buffer.contents();
+	assert!(output.contains("query") && output.contains("dropped"));
+	assert!(!output.contains("dbx") && !output.contains("connected"));
+
+	assert!(logger.is_enabled(Level::Debug, "app::db"));
+	assert!(!logger.is_enabled(Level::Debug, "app"));
+	assert!(!logger.is_enabled(Level::Info, "app::net::tcp"));
+}
+
+#[test]
+fn boost() {
+	let (logger, buffer) = test_logger();
+	let logger: &'static Logger<Buffer> = Box::leak(Box::new(logger));
+	logger.set_level(LevelFilter::Info);
+	logger.boost("db", LevelFilter::Trace);
+	log(logger, Level::Trace, "db::pool", "db trace");
+	log(logger, Level::Trace, "http", "http trace");
+	log(logger, Level::Info, "http", "http info");
+	let output = buffer.contents();
+	assert!(output.contains("db trace") && output.contains("http info"));
+	assert!(!output.contains("http trace"), "{output}");
+
+	// boosting again replaces the level
+	logger.boost("db", LevelFilter::Debug);
+	log(logger, Level::Trace, "db", "second db trace");
+	log(logger, Level::Debug, "db", "db debug");
+	logger.unboost("db");
+	log(logger, Level::Debug, "db", "unboosted");
+	let output = buffer.contents();
+	assert!(output.contains("db debug"), "{output}");
+	assert!(!output.contains("second db trace") && !output.contains("unboosted"));
+}
+
+#[test]
+fn rejects_without_locking_boosts() {
+	let (logger, _) = test_logger();
+	let logger: &'static Logger<Buffer> = Box::leak(Box::new(logger));
+	logger.set_level(LevelFilter::Info);
+	logger.boost("db", LevelFilter::Debug);
+	let guard = crate::lock(&logger.boosts);
+	let (sender, receiver) = mpsc::channel();
+	thread::spawn(move || {
+		let _ = sender.send(logger.is_enabled(Level::Trace, "db"));
+	});
+	let enabled = receiver.recv_timeout(Duration::from_secs(5));
+	drop(guard);
+	assert_eq!(enabled, Ok(false));
+
+	logger.unboost("db");
+	assert_eq!(
+		logger.max_boost.load(Ordering::Relaxed),
+		LevelFilter::Off as u8
+	);
+}
+
+#[test]
+fn null() {
+	let logger = Logger::null();
+	assert!(!logger.is_enabled(Level::Error, "app"));
+	log(&logger, Level::Error, "app", "nothing");
+}
+
+#[test]
+fn clone_with_output() {
+	let (mut logger, first) = test_logger();
+	logger.labels = ["TRC", "DBG", "INF", "WRN", "ERR"];
+	logger.single_line = true;
+	logger.add_target_level("app::db", LevelFilter::Warn);
+	let second = Buffer::default();
+	let clone = logger.clone_with_output(second.clone());
+	for logger in [&logger, &clone] {
+		log(logger, Level::Info, "app", "first\nsecond");
+		log(logger, Level::Info, "app::db", "filtered");
+	}
+	assert_eq!(first.contents(), second.contents());
+	assert!(first.contents().contains("INF app"));
+	assert!(!first.contents().contains("filtered"));
+}
+
+#[test]
+fn clone_with_output_writes() {
+	let buffer = Buffer::default();
+	let clone = Logger::null().clone_with_output(buffer.clone());
+	log(&clone, Level::Info, "app", "from null");
+	assert!(buffer.contents().contains("from null"));
+
+	let (sink, receiver) = crate::channel_sink();
+	let buffer = Buffer::default();
+	let clone = sink.clone_with_output(buffer.clone());
+	log(&clone, Level::Info, "app", "from sink");
+	assert!(buffer.contents().contains("from sink"));
+	assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn event() {
+	let (logger, buffer) = test_logger();
+	logger
+		.event(Level::Info, "app")
+		.field("user", 42)
+		.field("name", "ferris")
+		.message("logged in")
+		.emit();
+	let output = buffer.contents();
+	assert!(output.contains("logged in"));
+	assert!(output.contains("user: 42") && output.contains("name: \"ferris\""));
+}
+
+#[test]
+fn channel_sink() {
+	let (logger, receiver) = crate::channel_sink();
+	log(&logger, Level::Warn, "app::db", "slow query");
+	logger
+		.event(Level::Info, "app")
+		.field("rows", 3)
+		.message("done")
+		.emit();
+	let records: Vec<_> = receiver.try_iter().collect();
+	assert_eq!(records.len(), 2);
+	assert_eq!(records[0].level, Level::Warn);
+	assert_eq!(records[0].target, "app::db");
+	assert_eq!(records[0].message, "slow query");
+	assert_eq!(records[1].target, "app");
+	assert_eq!(records[1].file.as_deref(), Some(file!()));
+	assert_eq!(records[1].message, "done");
+	assert_eq!(records[1].fields, [("rows".to_owned(), "3".to_owned())]);
+}
+
+#[test]
+fn filters_events() {
+	let (logger, buffer) = test_logger();
+	logger.set_level(LevelFilter::Info);
+	logger.event(Level::Debug, "app").message("hidden").emit();
+	logger.event(Level::Info, "app").message("shown").emit();
+	let output = buffer.contents();
+	assert!(output.contains("shown") && !output.contains("hidden"));
+
+	let (logger, receiver) = crate::channel_sink();
+	logger.set_level(LevelFilter::Info);
+	logger.event(Level::Debug, "app").message("hidden").emit();
+	logger.event(Level::Info, "app").message("shown").emit();
+	let messages: Vec<_> = receiver.try_iter().map(|record| record.message).collect();
+	assert_eq!(messages, ["shown"]);
+}
+
+#[test]
+fn filters_own_records_by_target() {
+	let (mut logger, buffer) = test_logger();
+	logger.set_level(LevelFilter::Info);
+	logger.add_target_level("app::db", LevelFilter::Debug);
+	logger.add_target_level("app::net", LevelFilter::Off);
+	logger.success("app::net", format_args!("net success"));
+	logger.log_change(Level::Debug, "app::db", "db retries", &1, &2);
+	logger.log_change(Level::Debug, "app", "app retries", &1, &2);
+	let _ = logger.timed(Level::Info, "app::net::tcp", "connect", || Ok::<_, ()>(()));
+	logger
+		.event(Level::Debug, "app::db::pool")
+		.message("db event")
+		.emit();
+	logger.boost("app::net", LevelFilter::Info);
+	logger.success("app::net", format_args!("boosted success"));
+	let output = buffer.contents();
+	assert!(
+		output.contains("db retries") && output.contains("db event"),
+		"{output}"
+	);
+	assert!(output.contains("boosted success"), "{output}");
+	assert!(
+		!output.contains("net success") && !output.contains("app retries"),
+		"{output}"
+	);
+	assert!(!output.contains("connect"), "{output}");
+}
+
+#[test]
+fn replay() {
+	let (sink, receiver) = crate::channel_sink();
+	log(&sink, Level::Info, "app", "first");
+	log(&sink, Level::Error, "app", "second");
+	let (logger, buffer) = test_logger();
+	logger.replay(receiver.try_iter());
+	let output = buffer.contents();
+	assert!(output.contains("info app\n        first"), "{output}");
+	assert!(output.contains("error app\n        second"), "{output}");
+}
+
+#[test]
+fn replay_matches_event() {
+	fn emit<T: io::Write + Send + Sync + 'static>(logger: &Logger<T>) {
+		logger
+			.event(Level::Info, "app")
+			.field("rows", 3)
+			.message("done")
+			.emit();
+	}
+	let (logger, buffer) = test_logger();
+	emit(&logger);
+	let (sink, receiver) = crate::channel_sink();
+	emit(&sink);
+	let (replayed, replay_buffer) = test_logger();
+	replayed.replay(receiver.try_iter());
+	assert_eq!(replay_buffer.contents(), buffer.contents());
+}
+
+#[test]
+fn filters_replay() {
+	let records = [
+		record(Level::Debug, "app", "debug"),
+		record(Level::Info, "app", "info"),
+		record(Level::Info, "app::db", "db info"),
+		record(Level::Warn, "app::db", "db warn"),
+	];
+
+	let (mut logger, buffer) = test_logger();
+	logger.set_level(LevelFilter::Info);
+	logger.add_target_level("app::db", LevelFilter::Warn);
+	logger.replay(records.clone());
+	let output = buffer.contents();
+	assert!(output.contains("\n        info\n") && output.contains("db warn"));
+	assert!(
+		!output.contains("debug") && !output.contains("db info"),
+		"{output}"
+	);
+
+	let (mut logger, receiver) = crate::channel_sink();
+	logger.set_level(LevelFilter::Info);
+	logger.add_target_level("app::db", LevelFilter::Warn);
+	logger.replay(records);
+	let messages: Vec<_> = receiver.try_iter().map(|record| record.message).collect();
+	assert_eq!(messages, ["info", "db warn"]);
+}
+
+#[test]
+#[cfg(feature = "log")]
+fn log_records() {
+	use log::Log;
+
+	let (logger, buffer) = test_logger();
+	logger.log(
+		&log::Record::builder()
+			.level(log::Level::Warn)
+			.target("app::db")
+			.module_path(Some("app::db"))
+			.line(Some(7))
+			.args(format_args!("slow query"))
+			.build(),
+	);
+	let output = buffer.contents();
+	assert!(
+		output.contains("warn app/db:7\n        slow query"),
+		"{output}"
+	);
+}
+
+#[test]
+#[cfg(feature = "log")]
+fn skips_formatting_filtered_records() {
+	use log::Log;
+	use std::{fmt, sync::atomic::AtomicBool};
+
+	struct Expensive<'a>(&'a AtomicBool);
+
+	impl fmt::Display for Expensive<'_> {
+		fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+			self.0.store(true, Ordering::Relaxed);
+			f.write_str("expensive")
+		}
+	}
+
+	let (logger, buffer) = test_logger();
+	logger.set_level(LevelFilter::Info);
+	let formatted = AtomicBool::new(false);
+	logger.log(
+		&log::Record::builder()
+			.level(log::Level::Trace)
+			.args(format_args!("{}", Expensive(&formatted)))
+			.build(),
+	);
+	assert!(!formatted.load(Ordering::Relaxed));
+	assert_eq!(buffer.contents(), "");
+}
+
+#[test]
+#[cfg(feature = "kv")]
+fn kv_position() {
+	use log::Log;
+
+	let pairs = [("user", 42)];
+	let (mut logger, buffer) = test_logger();
+	let record = |logger: &Logger<Buffer>| {
+		logger.log(
+			&log::Record::builder()
+				.level(log::Level::Info)
+				.args(format_args!("logged in"))
+				.key_values(&pairs)
+				.build(),
+		)
+	};
+	record(&logger);
+	logger.kv_position = crate::FieldPosition::BeforeMessage;
+	record(&logger);
+	let output = buffer.contents();
+	let lines: Vec<_> = output.lines().map(str::trim).collect();
+	assert_eq!(lines[1..3], ["logged in", "user: 42"], "{output}");
+	assert_eq!(lines[4..6], ["user: 42", "logged in"], "{output}");
+}
+
+#[test]
+#[cfg(feature = "log")]
+fn split_logger() {
+	use log::Log;
+
+	let (out, out_buffer) = test_logger();
+	let (err, err_buffer) = test_logger();
+	let split = SplitLogger::new(out, err);
+	for (level, message) in [(log::Level::Info, "fine"), (log::Level::Error, "broken")] {
+		split.log(
+			&log::Record::builder()
+				.level(level)
+				.args(format_args!("{message}"))
+				.build(),
+		);
+	}
+	let (out, err) = (out_buffer.contents(), err_buffer.contents());
+	assert!(out.contains("fine") && !out.contains("broken"));
+	assert!(err.contains("broken") && !err.contains("fine"));
+}
+
+#[test]
+fn split_logger_default() {
+	let split = SplitLogger::default();
+	assert_eq!(split.threshold, Level::Warn);
+}
+
+#[test]
+#[cfg(all(feature = "release-max-level-info", not(debug_assertions)))]
+fn release_max_level_info() {
+	let (logger, buffer) = test_logger();
+	log(&logger, Level::Trace, "app", "dropped");
+	log(&logger, Level::Debug, "app", "dropped");
+	log(&logger, Level::Info, "app", "kept");
+	assert!(!buffer.contents().contains("dropped"));
+}
+
+#[cfg(feature = "network")]
+mod network {
+	use super::*;
+	use crate::NetworkWriter;
+	use std::{
+		io::{BufRead, BufReader},
+		net::TcpListener,
+		sync::mpsc,
+		time::Instant,
+	};
+
+	#[test]
+	fn sends_records() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let logger = Logger::network(NetworkWriter::tcp(listener.local_addr().unwrap()).unwrap());
+		logger.write_line("hello");
+		let (stream, _) = listener.accept().unwrap();
+		let mut line = String::new();
+		BufReader::new(stream).read_line(&mut line).unwrap();
+		assert_eq!(line, "hello\n");
+	}
+
+	#[test]
+	fn sends_records_longer_than_max_pending() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let writer = NetworkWriter::tcp(listener.local_addr().unwrap()).unwrap();
+		let logger = Logger::network(writer.with_max_pending(4));
+		logger.write_line("hello");
+		let (stream, _) = listener.accept().unwrap();
+		let mut line = String::new();
+		BufReader::new(stream).read_line(&mut line).unwrap();
+		assert_eq!(line, "hello\n");
+	}
+
+	#[test]
+	fn reconnects() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let logger = Logger::network(NetworkWriter::tcp(listener.local_addr().unwrap()).unwrap());
+		logger.write_line("first");
+		let (stream, _) = listener.accept().unwrap();
+		let mut line = String::new();
+		BufReader::new(&stream).read_line(&mut line).unwrap();
+		assert_eq!(line, "first\n");
+		drop(stream);
+
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			let (stream, _) = listener.accept().unwrap();
+			for line in BufReader::new(stream).lines() {
+				let _ = sender.send(line.unwrap());
+			}
+		});
+		// keep writing until the writer notices the old connection is gone and reconnects
+		let start = Instant::now();
+		while start.elapsed() < Duration::from_secs(10) {
+			logger.write_line("again");
+			if let Ok(line) = receiver.recv_timeout(Duration::from_millis(100)) {
+				assert_eq!(line, "again");
+				return;
+			}
+		}
+		panic!("never reconnected");
+	}
+}
+
+#[cfg(feature = "tracing")]
+mod layer {
+	use super::*;
+	use tracing::{span, subscriber, Event, Id, Subscriber};
+	use tracing_subscriber::{
+		registry::{Extensions, ExtensionsMut, LookupSpan, SpanData},
+		Layer,
+	};
+
+	/// A subscriber without spans, so a [`Logger`] can be tested as a layer without a registry.
+	struct NoSpans;
+
+	impl Subscriber for NoSpans {
+		fn enabled(&self, _: &tracing::Metadata) -> bool {
+			true
+		}
+
+		fn new_span(&self, _: &span::Attributes) -> Id {
+			Id::from_u64(1)
+		}
+
+		fn record(&self, _: &Id, _: &span::Record) {}
+
+		fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+		fn event(&self, _: &Event) {}
+
+		fn enter(&self, _: &Id) {}
+
+		fn exit(&self, _: &Id) {}
+	}
+
+	enum NoSpan {}
+
+	impl SpanData<'_> for NoSpan {
+		fn id(&self) -> Id {
+			match *self {}
+		}
+
+		fn metadata(&self) -> &'static tracing::Metadata<'static> {
+			match *self {}
+		}
+
+		fn parent(&self) -> Option<&Id> {
+			match *self {}
+		}
+
+		fn extensions(&self) -> Extensions<'_> {
+			match *self {}
+		}
+
+		fn extensions_mut(&self) -> ExtensionsMut<'_> {
+			match *self {}
+		}
+	}
+
+	impl LookupSpan<'_> for NoSpans {
+		type Data = NoSpan;
+
+		fn span_data(&self, _: &Id) -> Option<NoSpan> {
+			None
+		}
+	}
+
+	/// Run `f` with `logger` as the only layer.
+	pub(crate) fn with_logger<T: io::Write + Send + Sync + 'static>(
+		logger: Logger<T>,
+		f: impl FnOnce(),
+	) {
+		subscriber::with_default(logger.with_subscriber(NoSpans), f);
+	}
+
+	#[test]
+	fn events() {
+		let (logger, buffer) = test_logger();
+		with_logger(logger, || tracing::warn!(count = 3, "hello"));
+		let output = buffer.contents();
+		assert!(output.contains("warn tinylog/tests/layer:"), "{output}");
+		assert!(
+			output.contains("\n        hello\n        count: 3\n"),
+			"{output}"
+		);
+	}
+
+	#[test]
+	fn layered_loggers() {
+		let (first, first_buffer) = test_logger();
+		let (second, second_buffer) = test_logger();
+		let subscriber = first.and_then(second).with_subscriber(NoSpans);
+		subscriber::with_default(subscriber, || tracing::info!("both"));
+		assert!(first_buffer.contents().contains("both"));
+		assert!(second_buffer.contents().contains("both"));
+	}
+
+	#[test]
+	fn max_field_len() {
+		let (mut logger, buffer) = test_logger();
+		logger.max_field_len = Some(8);
+		let long = "a".repeat(100);
+		with_logger(logger, || tracing::info!(data = long, "short"));
+		let output = buffer.contents();
+		assert!(output.contains("data: \"aaaaaaa…(truncated)"), "{output}");
+		assert!(output.contains("short"));
+	}
+
+	#[test]
+	fn fmt_errors() {
+		for max_field_len in [None, Some(8)] {
+			let (mut logger, buffer) = test_logger();
+			logger.max_field_len = max_field_len;
+			with_logger(
+				logger,
+				|| tracing::info!(value = ?Unformattable, "{}", Unformattable),
+			);
+			let output = buffer.contents();
+			assert!(output.contains("\n        <fmt error>\n"), "{output}");
+			assert!(output.contains("value: <fmt error>"), "{output}");
+		}
+
+		let (logger, receiver) = crate::channel_sink();
+		with_logger(logger, || tracing::info!(value = ?Unformattable, "ok"));
+		let record = receiver.try_recv().unwrap();
+		assert_eq!(
+			record.fields,
+			[("value".to_owned(), "<fmt error>".to_owned())]
+		);
+	}
+
+	#[test]
+	fn no_span_id_outside_spans() {
+		let (mut logger, buffer) = test_logger();
+		logger.include_span_id = true;
+		with_logger(logger, || tracing::info!("outside"));
+		assert!(!buffer.contents().contains("span_id"));
+	}
+
+	#[test]
+	fn tracing_default_fields() {
+		let (mut logger, buffer) = test_logger();
+		logger.field_style = crate::FieldStyle::TracingDefault;
+		with_logger(logger, || tracing::info!(count = 3, name = "x", "hi"));
+		let output = buffer.contents();
+		assert_eq!(output.lines().count(), 1, "{output}");
+		assert!(output.ends_with(" hi count=3 name=\"x\"\n"), "{output}");
+	}
+
+	#[test]
+	fn max_fields() {
+		let (mut logger, buffer) = test_logger();
+		logger.max_fields = Some(3);
+		with_logger(logger, || {
+			tracing::info!(
+				a = 1,
+				b = 2,
+				c = 3,
+				d = 4,
+				e = 5,
+				f = 6,
+				g = 7,
+				h = 8,
+				i = 9,
+				j = 10,
+				"many"
+			)
+		});
+		let output = buffer.contents();
+		let lines: Vec<_> = output.lines().skip(1).map(str::trim).collect();
+		assert_eq!(lines, ["many", "a: 1", "b: 2", "c: 3", "… (+7 more)"]);
+	}
+
+	#[test]
+	fn dim() {
+		let record = |level| {
+			let (mut logger, buffer) = color_logger();
+			logger.dim = Some(Arc::new(|meta| *meta.level() >= tracing::Level::DEBUG));
+			with_logger(logger, || match level {
+				Level::Debug => tracing::debug!(count = 3, "hello"),
+				_ => tracing::info!(count = 3, "hello"),
+			});
+			let output = buffer.contents();
+			assert_ansi(&output);
+			output
+		};
+
+		let debug = record(Level::Debug);
+		for line in debug.lines() {
+			assert!(line.trim_start().starts_with("\x1b[2m"), "{debug:?}");
+			assert!(line.ends_with("\x1b[m"), "{debug:?}");
+		}
+		assert!(debug.contains("\x1b[2m        hello\x1b[m\n"), "{debug:?}");
+
+		let info = record(Level::Info);
+		assert!(!info.trim_start().starts_with("\x1b[2m"), "{info:?}");
+		assert!(
+			info.contains("\n        hello\n        count: 3\n"),
+			"{info:?}"
+		);
+
+		// off by default
+		let (logger, buffer) = color_logger();
+		with_logger(logger, || tracing::debug!("hello"));
+		let output = buffer.contents();
+		assert!(!output.trim_start().starts_with("\x1b[2m"), "{output:?}");
+		assert!(output.ends_with("\x1b[m\n        hello\n"), "{output:?}");
+	}
+
+	#[test]
+	fn channel_sink() {
+		let (logger, receiver) = crate::channel_sink();
+		with_logger(logger, || tracing::error!(rows = 3, "done"));
+		let record = receiver.try_recv().unwrap();
+		assert_eq!(record.level, Level::Error);
+		assert_eq!(record.message, "done");
+		assert_eq!(record.fields, [("rows".to_owned(), "3".to_owned())]);
+	}
+}
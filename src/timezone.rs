@@ -0,0 +1,156 @@
+use crate::util::StringLike;
+use std::{error::Error, fmt};
+use time::{OffsetDateTime, UtcOffset};
+
+/// An error returned when parsing a timezone offset fails.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimezoneError;
+
+impl fmt::Display for ParseTimezoneError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("invalid timezone offset, expected `Z`, `UTC`, or `±HH[:MM]`")
+	}
+}
+
+impl Error for ParseTimezoneError {}
+
+/// Parse `Z`, `UTC`, `±HH`, `±HHMM`, or `±HH:MM`.
+pub(super) fn parse_offset(s: &str) -> Result<UtcOffset, ParseTimezoneError> {
+	let s = s.trim();
+	if s.eq_ignore_ascii_case("z") || s.eq_ignore_ascii_case("utc") {
+		return Ok(UtcOffset::UTC);
+	}
+
+	let (negative, rest) = match s.as_bytes().first() {
+		Some(b'+') => (false, &s[1..]),
+		Some(b'-') => (true, &s[1..]),
+		_ => return Err(ParseTimezoneError),
+	};
+
+	if !rest.is_ascii() {
+		return Err(ParseTimezoneError);
+	}
+
+	let (hours, minutes) = match rest.len() {
+		2 => (rest, "00"),
+		4 => rest.split_at(2),
+		5 if rest.as_bytes()[2] == b':' => (&rest[..2], &rest[3..]),
+		_ => return Err(ParseTimezoneError),
+	};
+
+	let parse = |digits: &str| -> Result<i8, ParseTimezoneError> {
+		if !digits.bytes().all(|b| b.is_ascii_digit()) {
+			return Err(ParseTimezoneError);
+		}
+		digits.parse().map_err(|_| ParseTimezoneError)
+	};
+	let (mut hours, mut minutes) = (parse(hours)?, parse(minutes)?);
+	if minutes >= 60 {
+		return Err(ParseTimezoneError);
+	}
+	if negative {
+		hours = -hours;
+		minutes = -minutes;
+	}
+
+	UtcOffset::from_hms(hours, minutes, 0).map_err(|_| ParseTimezoneError)
+}
+
+/// Write an offset as `±HH:MM`.
+pub(super) fn push_offset<S: StringLike>(output: &mut S, offset: UtcOffset) {
+	output.push(if offset.is_negative() { '-' } else { '+' });
+	let hours = offset.whole_hours().unsigned_abs();
+	if hours < 10 {
+		output.push('0');
+	}
+	output.push_str(itoa::Buffer::new().format(hours));
+	output.push(':');
+	let minutes = offset.minutes_past_hour().unsigned_abs();
+	if minutes < 10 {
+		output.push('0');
+	}
+	output.push_str(itoa::Buffer::new().format(minutes));
+}
+
+/// Write a time like `2024-03-07T14:05:09Z`, or `2024-03-07T14:05:09+05:30`.
+pub(super) fn push_rfc3339<S: StringLike>(output: &mut S, time: OffsetDateTime) {
+	let push_padded = |output: &mut S, n: u8| {
+		if n < 10 {
+			output.push('0');
+		}
+		output.push_str(itoa::Buffer::new().format(n));
+	};
+
+	let year = time.year();
+	for _ in itoa::Buffer::new().format(year).len()..4 {
+		output.push('0');
+	}
+	output.push_str(itoa::Buffer::new().format(year));
+	output.push('-');
+	push_padded(output, time.month() as u8);
+	output.push('-');
+	push_padded(output, time.day());
+	output.push('T');
+	push_padded(output, time.hour());
+	output.push(':');
+	push_padded(output, time.minute());
+	output.push(':');
+	push_padded(output, time.second());
+	if time.offset().is_utc() {
+		output.push('Z');
+	} else {
+		push_offset(output, time.offset());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn offset(hours: i8, minutes: i8) -> UtcOffset {
+		UtcOffset::from_hms(hours, minutes, 0).unwrap()
+	}
+
+	#[test]
+	fn parses_offsets() {
+		assert_eq!(parse_offset("+05:30"), Ok(offset(5, 30)));
+		assert_eq!(parse_offset("-08:00"), Ok(offset(-8, 0)));
+		assert_eq!(parse_offset("-0800"), Ok(offset(-8, 0)));
+		assert_eq!(parse_offset("+09"), Ok(offset(9, 0)));
+		assert_eq!(parse_offset("Z"), Ok(UtcOffset::UTC));
+		assert_eq!(parse_offset("z"), Ok(UtcOffset::UTC));
+		assert_eq!(parse_offset(" UTC "), Ok(UtcOffset::UTC));
+	}
+
+	#[test]
+	fn parses_negative_offsets_under_an_hour() {
+		let parsed = parse_offset("-00:30").unwrap();
+		assert_eq!(parsed, offset(0, -30));
+		assert!(parsed.is_negative());
+		let mut output = String::new();
+		push_offset(&mut output, parsed);
+		assert_eq!(output, "-00:30");
+	}
+
+	#[test]
+	fn rejects_invalid_offsets() {
+		for invalid in [
+			"", "05:30", "+5:30", "+05:3", "+05-30", "+05:3x", "+0a:00", "+05:٣٠", "+26:00",
+			"utc+1",
+		] {
+			assert_eq!(
+				parse_offset(invalid),
+				Err(ParseTimezoneError),
+				"{invalid:?}"
+			);
+		}
+	}
+
+	#[test]
+	fn rejects_minutes_over_59() {
+		assert_eq!(parse_offset("+05:60"), Err(ParseTimezoneError));
+		assert_eq!(parse_offset("-0099"), Err(ParseTimezoneError));
+		assert_eq!(parse_offset("+05:59"), Ok(offset(5, 59)));
+	}
+}
@@ -1,23 +1,68 @@
+mod timing;
 mod visitor;
 
-use self::visitor::FieldVisitor;
+use self::{
+	timing::SpanTiming,
+	visitor::{push_field_name, CollectVisitor, FieldVisitor, SpanFieldVisitor},
+};
 use crate::{
-	util::{with_local_buf, Indented, StringLike},
-	Logger, PrefixOptions,
+	push_suppressed,
+	util::{push_duration, with_local_buf, Indented, StringLike},
+	Logger, PrefixOptions, Style,
 };
-use std::io;
+use std::{io, time::SystemTime};
 use tracing::{
 	span::{Attributes, Record},
 	Event, Id, Subscriber,
 };
 use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
 
-#[cfg(feature = "timestamps")]
-use std::time::SystemTime;
-
 struct SpanData {
-	content: String,
-	prefix_end_index: usize,
+	prefix: String,
+	/// Field names, and their formatted contents.
+	fields: Vec<(&'static str, String)>,
+	/// Only if `span_timings` is enabled.
+	timing: Option<SpanTiming>,
+}
+
+impl<T: io::Write + Send + Sync + 'static> Logger<T> {
+	fn with_timing<S, F>(&self, id: &Id, ctx: &Context<S>, f: F)
+	where
+		S: Subscriber + for<'any> LookupSpan<'any>,
+		F: FnOnce(&mut SpanTiming),
+	{
+		if !self.span_timings {
+			return;
+		}
+		let span = ctx.span(id).expect("span missing");
+		let mut extensions = span.extensions_mut();
+		if let Some(timing) = extensions
+			.get_mut::<SpanDataList>()
+			.and_then(|list| list.get_mut(self.id))
+			.and_then(|data| data.timing.as_mut())
+		{
+			f(timing);
+		}
+	}
+}
+
+/// The [`SpanData`] of each [`Logger`] in the subscriber, so several can be layered together.
+struct SpanDataList(Vec<(usize, SpanData)>);
+
+impl SpanDataList {
+	fn get(&self, id: usize) -> Option<&SpanData> {
+		self.0
+			.iter()
+			.find(|(logger_id, _)| *logger_id == id)
+			.map(|(_, data)| data)
+	}
+
+	fn get_mut(&mut self, id: usize) -> Option<&mut SpanData> {
+		self.0
+			.iter_mut()
+			.find(|(logger_id, _)| *logger_id == id)
+			.map(|(_, data)| data)
+	}
 }
 
 impl<S, T: io::Write + Send + Sync + 'static> Layer<S> for Logger<T>
@@ -25,41 +70,113 @@ where
 	S: Subscriber + for<'any> LookupSpan<'any>,
 {
 	fn on_new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
+		if self.discard {
+			return;
+		}
 		let span = ctx.span(id).expect("span missing");
 
-		let mut content = String::new();
+		let mut prefix = String::new();
 		self.write_prefix(
-			&mut content,
+			&mut prefix,
 			&attrs.metadata().into(),
 			&PrefixOptions {
 				align: false,
+				dim: false,
+				delta: None,
+				icon: None,
 				#[cfg(feature = "timestamps")]
 				time: None,
 			},
 		);
-		let prefix_end_index = content.len();
-		attrs.record(&mut FieldVisitor::new(&mut content));
+		let mut fields = Vec::new();
+		attrs.record(&mut SpanFieldVisitor::new(&mut fields, self.max_field_len));
 
+		let data = SpanData {
+			prefix,
+			fields,
+			timing: self.span_timings.then(SpanTiming::new),
+		};
 		let mut extensions = span.extensions_mut();
-		extensions.insert(SpanData {
-			content,
-			prefix_end_index,
-		});
+		match extensions.get_mut::<SpanDataList>() {
+			Some(list) => list.0.push((self.id, data)),
+			None => extensions.insert(SpanDataList(vec![(self.id, data)])),
+		}
 	}
 
 	fn on_record(&self, id: &Id, values: &Record, ctx: Context<S>) {
+		if self.discard {
+			return;
+		}
 		let span = ctx.span(id).expect("span missing");
 		let mut extensions = span.extensions_mut();
-		let data: &mut SpanData = extensions
-			.get_mut()
+		let data = extensions
+			.get_mut::<SpanDataList>()
+			.and_then(|list| list.get_mut(self.id))
 			.expect("span missing SpanData extension");
-		values.record(&mut FieldVisitor::new(&mut data.content));
+		values.record(&mut SpanFieldVisitor::new(
+			&mut data.fields,
+			self.max_field_len,
+		));
+	}
+
+	fn on_enter(&self, id: &Id, ctx: Context<S>) {
+		self.with_timing(id, &ctx, SpanTiming::enter);
+	}
+
+	fn on_exit(&self, id: &Id, ctx: Context<S>) {
+		self.with_timing(id, &ctx, SpanTiming::exit);
+	}
+
+	fn on_close(&self, id: Id, ctx: Context<S>) {
+		if !self.span_timings {
+			return;
+		}
+		let span = ctx.span(&id).expect("span missing");
+		let extensions = span.extensions();
+		let Some(timing) = extensions
+			.get::<SpanDataList>()
+			.and_then(|list| list.get(self.id))
+			.and_then(|data| data.timing.as_ref())
+		else {
+			return;
+		};
+
+		self.write_record(&span.metadata().into(), None, |output| {
+			output.push('\n');
+			output.push_str(span.name());
+			output.push_str(" closed:");
+			if let Some(cpu) = timing.cpu() {
+				output.push_str(" cpu=");
+				push_duration(output, cpu);
+			}
+			output.push_str(" wall=");
+			push_duration(output, timing.wall());
+		});
 	}
 
 	fn on_event(&self, event: &Event, ctx: Context<S>) {
+		let level = (*event.metadata().level()).into();
+		if !self.is_enabled(level, event.metadata().target()) {
+			return;
+		}
+		if self.channel.is_some() {
+			let (mut message, mut fields) = (String::new(), Vec::new());
+			event.record(&mut CollectVisitor {
+				message: &mut message,
+				fields: &mut fields,
+			});
+			self.send_record(&event.metadata().into(), SystemTime::now(), message, fields);
+			return;
+		}
+		let Some(suppressed) = self.check_throttle(level) else {
+			return;
+		};
 		#[cfg(feature = "timestamps")]
 		let time = SystemTime::now();
 
+		let dim = self.color && self.dim.as_ref().is_some_and(|dim| dim(event.metadata()));
+		let delta = self.delta();
+
 		with_local_buf(move |mut buf| {
 			buf.clear();
 
@@ -68,40 +185,90 @@ where
 				&event.metadata().into(),
 				&PrefixOptions {
 					align: true,
+					dim,
+					delta,
+					icon: None,
 					#[cfg(feature = "timestamps")]
-					time: Some(time),
+					time: self.timestamp_from.allows(level).then_some(time),
 				},
 			);
+			let prefix_len = buf.len();
 
 			let mut i_buf = Indented::new(&mut buf, 8);
-			event.record(&mut FieldVisitor::new(&mut i_buf));
+			i_buf.escape = self.single_line;
+			let mut visitor = FieldVisitor::new(&mut i_buf, self.max_field_len, self.field_style)
+				.with_max_fields(self.max_fields)
+				.remember_shown(self.dedup_fields);
+			event.record(&mut visitor);
+			visitor.finish();
+			// names of fields that were already shown, if `dedup_fields` is enabled. fields hidden by
+			// `max_fields` don't count, so the span's value is shown instead
+			let mut shown_fields = visitor.shown().to_vec();
 
-			if let Some(parent_span) = ctx.event_span(event) {
+			let parent_span = ctx.event_span(event);
+			if let (true, Some(parent_span)) = (self.include_span_id, &parent_span) {
+				push_field_name(&mut i_buf, self.field_style, "span_id");
+				i_buf.push_str(itoa::Buffer::new().format(parent_span.id().into_u64()));
+			}
+			self.push_static_fields(&mut i_buf);
+			push_suppressed(&mut i_buf, suppressed);
+
+			if let Some(parent_span) = parent_span {
 				for span in parent_span.scope() {
 					let extensions = span.extensions();
-					let data: &SpanData =
-						extensions.get().expect("span missing SpanData extension");
-					let (prefix, fields) = data.content.split_at(data.prefix_end_index);
+					let data = extensions
+						.get::<SpanDataList>()
+						.and_then(|list| list.get(self.id))
+						.expect("span missing SpanData extension");
 					i_buf.indent -= 2;
 					i_buf.push('\n');
-					i_buf.push_str(prefix);
+					i_buf.push_str(&data.prefix);
+					if dim {
+						// the span's prefix ends with a reset
+						i_buf.push_str("\x1b[2m");
+					}
 					i_buf.indent += 2;
 
 					let name = span.name();
 					if !name.is_empty() {
 						i_buf.push('\n');
+						let styled = self.color && self.span_name_style != Style::default();
+						if styled {
+							let mut style = self.span_name_style;
+							style.color =
+								style.color.map(|color| color.downgrade(self.color_depth));
+							style.push_sgr(&mut i_buf);
+						}
 						i_buf.push_str(name);
+						if styled {
+							// reset
+							i_buf.push_str(if dim { "\x1b[;2m" } else { "\x1b[m" });
+						}
+					}
+					for (name, field) in &data.fields {
+						if !shown_fields.contains(name) {
+							// the leading newline separates fields, it isn't part of the content
+							i_buf.push('\n');
+							i_buf.push_str(field.strip_prefix('\n').unwrap_or(field));
+						}
+					}
+					if self.dedup_fields {
+						shown_fields.extend(data.fields.iter().map(|(name, _)| *name));
 					}
-					i_buf.push_str(fields);
 				}
 			}
 
-			buf.push('\n');
-			#[allow(unused_mut)]
-			let mut output = self.output.lock();
-			#[cfg(not(feature = "parking_lot"))]
-			let mut output = output.unwrap_or_else(|e| e.into_inner());
-			output.write_all(buf.as_bytes()).expect("io error");
+			self.inline_content(buf, prefix_len);
+			if dim {
+				// reset at the end of every line, so the dim doesn't leak into anything after it
+				let content = buf.split_off(prefix_len);
+				buf.push_str(&content.replace('\n', "\x1b[m\n\x1b[2m"));
+				buf.push_str("\x1b[m");
+			}
+			if let Some(transform) = &self.transform {
+				transform(buf);
+			}
+			self.write_output(buf, Some(level));
 		})
 	}
 }
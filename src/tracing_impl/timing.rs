@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+/// How long a span took, see [`Logger::span_timings`](crate::Logger::span_timings).
+pub(super) struct SpanTiming {
+	created: Instant,
+	/// CPU time spent inside of the span, or [`None`] if it can't be measured on this platform.
+	cpu: Option<Duration>,
+	/// When the span was last entered, and the thread's CPU time at that point.
+	entered: Option<Option<Duration>>,
+}
+
+impl SpanTiming {
+	pub fn new() -> Self {
+		Self {
+			created: Instant::now(),
+			cpu: thread_cpu_time().map(|_| Duration::ZERO),
+			entered: None,
+		}
+	}
+
+	pub fn enter(&mut self) {
+		self.entered = Some(thread_cpu_time());
+	}
+
+	pub fn exit(&mut self) {
+		if let Some(start) = self.entered.take() {
+			self.cpu = match (self.cpu, start, thread_cpu_time()) {
+				(Some(cpu), Some(start), Some(end)) => Some(cpu + end.saturating_sub(start)),
+				_ => None,
+			};
+		}
+	}
+
+	/// Time since the span was created.
+	pub fn wall(&self) -> Duration {
+		self.created.elapsed()
+	}
+
+	pub fn cpu(&self) -> Option<Duration> {
+		self.cpu
+	}
+}
+
+/// How much CPU time the current thread has used.
+///
+/// This is called every time a span is entered or exited, so each thread keeps its schedstat file
+/// open and reads it with a single `pread`. `clock_gettime` would avoid the syscall's file
+/// handling, but can't be called without `unsafe`.
+#[cfg(target_os = "linux")]
+fn thread_cpu_time() -> Option<Duration> {
+	use std::{fs::File, os::unix::fs::FileExt};
+
+	thread_local! {
+		static SCHEDSTAT: Option<File> = File::open("/proc/thread-self/schedstat").ok();
+	}
+
+	let mut buf = [0; 64];
+	let len = SCHEDSTAT
+		.with(|file| file.as_ref()?.read_at(&mut buf, 0).ok())
+		.filter(|&len| len > 0)?;
+	// the first number is the time spent on the cpu, in nanoseconds
+	let schedstat = std::str::from_utf8(&buf[..len]).ok()?;
+	let nanos = schedstat.split_whitespace().next()?.parse().ok()?;
+	Some(Duration::from_nanos(nanos))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_cpu_time() -> Option<Duration> {
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn measures_cpu_time() {
+		let mut timing = SpanTiming::new();
+		timing.enter();
+		let start = Instant::now();
+		let mut n = 0u64;
+		while start.elapsed() < Duration::from_millis(20) {
+			n = std::hint::black_box(n.wrapping_add(1));
+		}
+		timing.exit();
+		// only more than zero if the open file is actually read again
+		assert!(timing.cpu().unwrap() > Duration::ZERO);
+		assert!(timing.wall() >= Duration::from_millis(20));
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	#[test]
+	fn no_cpu_time() {
+		let mut timing = SpanTiming::new();
+		timing.enter();
+		std::thread::sleep(Duration::from_millis(1));
+		timing.exit();
+		assert_eq!(timing.cpu(), None);
+		assert!(timing.wall() >= Duration::from_millis(1));
+	}
+}
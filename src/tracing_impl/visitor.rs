@@ -1,67 +1,368 @@
-use crate::util::StringLike;
-use std::fmt;
+use crate::{
+	util::{format_lossy, write_args, StringLike, FMT_ERROR},
+	FieldStyle,
+};
+use std::fmt::{self, Write as _};
 use tracing::field::{Field, Visit};
 
-pub(super) struct FieldVisitor<T: StringLike + fmt::Write>(T);
+/// Write the start of a field named `name` in the given style, like `\nname: ` or ` name=`.
+pub(super) fn push_field_name<S: StringLike>(output: &mut S, style: FieldStyle, name: &str) {
+	match style {
+		FieldStyle::Multiline => {
+			output.push('\n');
+			output.push_str(name);
+			output.push_str(": ");
+		},
+		FieldStyle::TracingDefault => {
+			output.push(' ');
+			output.push_str(name);
+			output.push('=');
+		},
+	}
+}
+
+pub(super) struct FieldVisitor<T: StringLike + fmt::Write> {
+	output: T,
+	max_len: Option<usize>,
+	style: FieldStyle,
+	/// How many more fields to show, not counting `message`.
+	remaining: Option<usize>,
+	/// How many fields were left out because of `remaining`.
+	skipped: usize,
+	/// Names of the fields that were written, if [`remember_shown`](Self::remember_shown) was called.
+	shown: Option<Vec<&'static str>>,
+}
 
 impl<T: StringLike + fmt::Write> FieldVisitor<T> {
-	pub fn new(output: T) -> Self {
-		Self(output)
+	pub fn new(output: T, max_len: Option<usize>, style: FieldStyle) -> Self {
+		Self {
+			output,
+			max_len,
+			style,
+			remaining: None,
+			skipped: 0,
+			shown: None,
+		}
+	}
+
+	/// Only show this many fields, not counting `message`. Call [`finish`](Self::finish) afterwards.
+	pub fn with_max_fields(mut self, max_fields: Option<usize>) -> Self {
+		self.remaining = max_fields;
+		self
+	}
+
+	/// Keep track of which fields were written, see [`shown`](Self::shown).
+	pub fn remember_shown(mut self, enabled: bool) -> Self {
+		self.shown = enabled.then(Vec::new);
+		self
+	}
+
+	/// Names of the fields that were written, not counting ones hidden by
+	/// [`with_max_fields`](Self::with_max_fields).
+	pub fn shown(&self) -> &[&'static str] {
+		self.shown.as_deref().unwrap_or_default()
+	}
+
+	/// Write how many fields were left out, if any.
+	pub fn finish(&mut self) {
+		if self.skipped > 0 {
+			self.output.push(match self.style {
+				FieldStyle::Multiline => '\n',
+				FieldStyle::TracingDefault => ' ',
+			});
+			self.output.push_str("… (+");
+			self.output
+				.push_str(itoa::Buffer::new().format(self.skipped));
+			self.output.push_str(" more)");
+		}
 	}
 
-	fn write_field<'a>(&mut self, field: &'a Field) -> &'a str {
-		self.0.push('\n');
+	/// Write the start of a field, or return [`None`] if it shouldn't be shown.
+	fn write_field(&mut self, field: &Field) -> Option<&'static str> {
 		let name = field.name();
 		if name != "message" {
-			self.0.push_str(name);
-			self.0.push_str(": ");
+			match &mut self.remaining {
+				Some(0) => {
+					self.skipped += 1;
+					return None;
+				},
+				Some(remaining) => *remaining -= 1,
+				None => (),
+			}
+		}
+		if let Some(shown) = &mut self.shown {
+			shown.push(name);
 		}
-		name
+		if name == "message" {
+			self.output.push(match self.style {
+				FieldStyle::Multiline => '\n',
+				FieldStyle::TracingDefault => ' ',
+			});
+		} else {
+			push_field_name(&mut self.output, self.style, name);
+		}
+		Some(name)
+	}
+
+	fn write_value(&mut self, args: fmt::Arguments) {
+		let Some(max_len) = self.max_len else {
+			write_args(&mut self.output, args);
+			return;
+		};
+
+		let mut capped = Capped {
+			output: &mut self.output,
+			remaining: max_len,
+			truncated: false,
+		};
+		if capped.write_fmt(args).is_err() {
+			let marker = if capped.truncated {
+				"…(truncated)"
+			} else {
+				FMT_ERROR
+			};
+			self.output.push_str(marker);
+		}
+	}
+}
+
+/// Stops formatting once a certain number of bytes have been written.
+struct Capped<'a, T> {
+	output: &'a mut T,
+	remaining: usize,
+	truncated: bool,
+}
+
+impl<T: StringLike> fmt::Write for Capped<'_, T> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		if s.len() <= self.remaining {
+			self.output.push_str(s);
+			self.remaining -= s.len();
+			return Ok(());
+		}
+
+		let mut end = self.remaining;
+		while !s.is_char_boundary(end) {
+			end -= 1;
+		}
+		self.output.push_str(&s[..end]);
+		self.remaining = 0;
+		self.truncated = true;
+		Err(fmt::Error)
 	}
 }
 
 impl<T: StringLike + fmt::Write> Visit for FieldVisitor<T> {
 	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-		self.write_field(field);
-		write!(self.0, "{value:?}").expect("fmt error");
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.write_value(format_args!("{value:?}"));
 	}
 
 	fn record_str(&mut self, field: &Field, value: &str) {
-		let name = self.write_field(field);
+		let Some(name) = self.write_field(field) else {
+			return;
+		};
 		if name == "message" {
-			self.0.push_str(value);
+			match self.max_len {
+				Some(max_len) if value.len() > max_len => self.write_value(format_args!("{value}")),
+				_ => self.output.push_str(value),
+			}
 		} else {
-			write!(self.0, "{value:?}").expect("fmt error");
+			self.write_value(format_args!("{value:?}"));
 		}
 	}
 
 	fn record_bool(&mut self, field: &Field, value: bool) {
-		self.write_field(field);
-		self.0.push_str(if value { "true" } else { "false" });
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(if value { "true" } else { "false" });
 	}
 
 	fn record_u64(&mut self, field: &Field, value: u64) {
-		self.write_field(field);
-		self.0.push_str(itoa::Buffer::new().format(value));
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(itoa::Buffer::new().format(value));
 	}
 
 	fn record_u128(&mut self, field: &Field, value: u128) {
-		self.write_field(field);
-		self.0.push_str(itoa::Buffer::new().format(value));
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(itoa::Buffer::new().format(value));
 	}
 
 	fn record_i64(&mut self, field: &Field, value: i64) {
-		self.write_field(field);
-		self.0.push_str(itoa::Buffer::new().format(value));
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(itoa::Buffer::new().format(value));
 	}
 
 	fn record_i128(&mut self, field: &Field, value: i128) {
-		self.write_field(field);
-		self.0.push_str(itoa::Buffer::new().format(value));
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(itoa::Buffer::new().format(value));
 	}
 
 	fn record_f64(&mut self, field: &Field, value: f64) {
-		self.write_field(field);
-		self.0.push_str(ryu::Buffer::new().format(value));
+		if self.write_field(field).is_none() {
+			return;
+		}
+		self.output.push_str(ryu::Buffer::new().format(value));
+	}
+}
+
+/// Records each field into its own string, so they can be looked up (and replaced) by name later.
+pub(super) struct SpanFieldVisitor<'a> {
+	fields: &'a mut Vec<(&'static str, String)>,
+	max_len: Option<usize>,
+}
+
+impl<'a> SpanFieldVisitor<'a> {
+	pub fn new(fields: &'a mut Vec<(&'static str, String)>, max_len: Option<usize>) -> Self {
+		Self { fields, max_len }
+	}
+
+	fn record<F>(&mut self, field: &Field, f: F)
+	where
+		F: FnOnce(&mut FieldVisitor<&mut String>),
+	{
+		let mut content = String::new();
+		f(&mut FieldVisitor::new(
+			&mut content,
+			self.max_len,
+			FieldStyle::Multiline,
+		));
+		// recording a field again replaces its old value
+		match self
+			.fields
+			.iter_mut()
+			.find(|(name, _)| *name == field.name())
+		{
+			Some((_, old)) => *old = content,
+			None => self.fields.push((field.name(), content)),
+		}
+	}
+}
+
+impl Visit for SpanFieldVisitor<'_> {
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		self.record(field, |visitor| visitor.record_debug(field, value));
+	}
+
+	fn record_str(&mut self, field: &Field, value: &str) {
+		self.record(field, |visitor| visitor.record_str(field, value));
+	}
+
+	fn record_bool(&mut self, field: &Field, value: bool) {
+		self.record(field, |visitor| visitor.record_bool(field, value));
+	}
+
+	fn record_u64(&mut self, field: &Field, value: u64) {
+		self.record(field, |visitor| visitor.record_u64(field, value));
+	}
+
+	fn record_u128(&mut self, field: &Field, value: u128) {
+		self.record(field, |visitor| visitor.record_u128(field, value));
+	}
+
+	fn record_i64(&mut self, field: &Field, value: i64) {
+		self.record(field, |visitor| visitor.record_i64(field, value));
+	}
+
+	fn record_i128(&mut self, field: &Field, value: i128) {
+		self.record(field, |visitor| visitor.record_i128(field, value));
+	}
+
+	fn record_f64(&mut self, field: &Field, value: f64) {
+		self.record(field, |visitor| visitor.record_f64(field, value));
+	}
+}
+
+/// Collects the message and fields as plain strings, for a [`channel_sink`](crate::channel_sink).
+pub(super) struct CollectVisitor<'a> {
+	pub message: &'a mut String,
+	pub fields: &'a mut Vec<(String, String)>,
+}
+
+impl Visit for CollectVisitor<'_> {
+	fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+		if field.name() == "message" {
+			write_args(self.message, format_args!("{value:?}"));
+		} else {
+			self.fields.push((
+				field.name().to_owned(),
+				format_lossy(format_args!("{value:?}")),
+			));
+		}
+	}
+
+	fn record_str(&mut self, field: &Field, value: &str) {
+		if field.name() == "message" {
+			self.message.push_str(value);
+		} else {
+			self.fields
+				.push((field.name().to_owned(), value.to_owned()));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::FieldStyle;
+	use std::sync::Mutex;
+	use tracing::{span, subscriber, Event, Metadata, Subscriber};
+
+	/// Runs a [`FieldVisitor`] on every event, and keeps the names of the fields it wrote.
+	struct Shown(Mutex<Vec<&'static str>>);
+
+	impl Subscriber for Shown {
+		fn enabled(&self, _: &Metadata) -> bool {
+			true
+		}
+
+		fn new_span(&self, _: &span::Attributes) -> span::Id {
+			span::Id::from_u64(1)
+		}
+
+		fn record(&self, _: &span::Id, _: &span::Record) {}
+
+		fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+		fn event(&self, event: &Event) {
+			let mut output = String::new();
+			let mut visitor = FieldVisitor::new(&mut output, None, FieldStyle::Multiline)
+				.with_max_fields(Some(2))
+				.remember_shown(true);
+			event.record(&mut visitor);
+			*self.0.lock().unwrap() = visitor.shown().to_vec();
+		}
+
+		fn enter(&self, _: &span::Id) {}
+
+		fn exit(&self, _: &span::Id) {}
+	}
+
+	#[test]
+	fn shown_leaves_out_hidden_fields() {
+		let shown = std::sync::Arc::new(Shown(Mutex::new(Vec::new())));
+		subscriber::with_default(shown.clone(), || {
+			tracing::info!(a = 1, b = 2, c = 3, d = 4, "hello");
+		});
+		assert_eq!(*shown.0.lock().unwrap(), ["message", "a", "b"]);
+	}
+
+	#[test]
+	fn field_name_follows_style() {
+		let mut output = String::new();
+		push_field_name(&mut output, FieldStyle::Multiline, "span_id");
+		push_field_name(&mut output, FieldStyle::TracingDefault, "span_id");
+		assert_eq!(output, "\nspan_id:  span_id=");
 	}
 }
@@ -1,11 +1,11 @@
-use std::{cell::RefCell, fmt};
+use std::{cell::RefCell, fmt, time::Duration};
 
 pub(super) fn with_local_buf<F, R>(f: F) -> R
 where
 	F: FnOnce(&mut String) -> R,
 {
 	thread_local! {
-		static BUF: RefCell<String> = RefCell::new(String::new());
+		static BUF: RefCell<String> = const { RefCell::new(String::new()) };
 	}
 
 	let mut f = Some(f);
@@ -20,6 +20,126 @@ where
 	.unwrap_or_else(|| f.take().unwrap()(&mut String::default()))
 }
 
+/// Write a record's message on its own line, if it isn't empty.
+pub(super) fn push_message<S: StringLike + fmt::Write>(output: &mut S, args: fmt::Arguments) {
+	match args.as_str() {
+		Some(str) if !str.is_empty() => {
+			output.push('\n');
+			output.push_str(str);
+		},
+		None => {
+			output.push('\n');
+			write_args(output, args);
+		},
+		_ => (),
+	}
+}
+
+/// Write an already-formatted message and fields, each on its own line, like a [`LogRecord`].
+///
+/// [`LogRecord`]: crate::LogRecord
+pub(super) fn push_owned_fields<S: StringLike>(
+	output: &mut S,
+	message: &str,
+	fields: &[(String, String)],
+) {
+	if !message.is_empty() {
+		output.push('\n');
+		output.push_str(message);
+	}
+	for (name, value) in fields {
+		output.push('\n');
+		output.push_str(name);
+		output.push_str(": ");
+		output.push_str(value);
+	}
+}
+
+/// Written in place of a value whose `Display` or `Debug` impl returned an error.
+pub(super) const FMT_ERROR: &str = "<fmt error>";
+
+/// Write `args`, or add [`FMT_ERROR`] if formatting fails, instead of panicking.
+pub(super) fn write_args<S: fmt::Write>(output: &mut S, args: fmt::Arguments) {
+	if output.write_fmt(args).is_err() {
+		let _ = output.write_str(FMT_ERROR);
+	}
+}
+
+/// Like [`format!`], but never panics, see [`write_args`].
+pub(super) fn format_lossy(args: fmt::Arguments) -> String {
+	let mut output = String::new();
+	write_args(&mut output, args);
+	output
+}
+
+/// Roughly how many columns `s` takes up in a terminal.
+///
+/// This only knows about the common cases: combining marks and variation selectors take no space,
+/// while CJK characters, most emoji, and anything followed by an emoji variation selector take two.
+pub(super) fn display_width(s: &str) -> usize {
+	let width = s
+		.chars()
+		.map(|c| match c as u32 {
+			0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F => 0,
+			0x1100..=0x115F
+			| 0x2E80..=0xA4CF
+			| 0xAC00..=0xD7A3
+			| 0xF900..=0xFAFF
+			| 0xFE30..=0xFE4F
+			| 0xFF00..=0xFF60
+			| 0xFFE0..=0xFFE6
+			| 0x1F300..=0x1F64F
+			| 0x1F900..=0x1F9FF
+			| 0x20000..=0x3FFFD => 2,
+			_ => 1,
+		})
+		.sum();
+	if width == 1 && s.contains('\u{FE0F}') {
+		2
+	} else {
+		width
+	}
+}
+
+/// Remove `\x1b[...m`-style escape sequences.
+pub(super) fn strip_ansi(s: &str) -> String {
+	let mut stripped = String::with_capacity(s.len());
+	let mut chars = s.chars();
+	while let Some(c) = chars.next() {
+		if c == '\x1b' {
+			if chars.next() == Some('[') {
+				// parameters, then a final letter
+				for c in chars.by_ref() {
+					if c.is_ascii_alphabetic() {
+						break;
+					}
+				}
+			}
+		} else {
+			stripped.push(c);
+		}
+	}
+	stripped
+}
+
+/// The terminal's width in columns, from `$COLUMNS`, or 80 if that isn't set.
+pub(super) fn terminal_width() -> usize {
+	std::env::var("COLUMNS")
+		.ok()
+		.and_then(|columns| columns.trim().parse().ok())
+		.filter(|&columns| columns > 0)
+		.unwrap_or(80)
+}
+
+/// The terminal's height in lines, from `$LINES`, or 24 if that isn't set.
+pub(super) fn terminal_height() -> usize {
+	std::env::var("LINES")
+		.ok()
+		.and_then(|lines| lines.trim().parse().ok())
+		.filter(|&lines| lines > 0)
+		.unwrap_or(24)
+}
+
 /// Similar to [`std::fmt::Write`], but with infallible methods.
 pub(super) trait StringLike {
 	fn push(&mut self, c: char);
@@ -55,24 +175,75 @@ impl StringLike for String {
 	}
 }
 
+/// Writes a space in place of every character written to it.
+#[cfg(feature = "timestamps")]
+pub(super) struct Blank<T>(pub T);
+
+#[cfg(feature = "timestamps")]
+impl<T: StringLike> StringLike for Blank<T> {
+	fn push(&mut self, _: char) {
+		self.0.push(' ');
+	}
+
+	fn push_str(&mut self, s: &str) {
+		for _ in s.chars() {
+			self.0.push(' ');
+		}
+	}
+
+	fn reserve(&mut self, additional: usize) {
+		self.0.reserve(additional);
+	}
+}
+
 /// Indents all text written to it by a certain amount.
+///
+/// A `'\n'` written with [`push`](StringLike::push) starts a new line; anything else is content.
 #[non_exhaustive]
 pub(super) struct Indented<T> {
 	pub output: T,
 
 	/// How many spaces to indent by.
 	pub indent: usize,
+
+	/// Keep everything on one line: new lines become a single space, and newlines, tabs, and
+	/// carriage returns in the content are escaped.
+	pub escape: bool,
 }
 
 impl<T> Indented<T> {
 	pub fn new(output: T, indent: usize) -> Self {
-		Self { output, indent }
+		Self {
+			output,
+			indent,
+			escape: false,
+		}
+	}
+}
+
+impl<T: StringLike> Indented<T> {
+	fn push_escaped(&mut self, s: &str) {
+		let mut rest = s;
+		while let Some(index) = rest.find(['\n', '\t', '\r']) {
+			self.output.push_str(&rest[..index]);
+			self.output.push_str(match rest.as_bytes()[index] {
+				b'\n' => "\\n",
+				b'\t' => "\\t",
+				_ => "\\r",
+			});
+			rest = &rest[index + 1..];
+		}
+		self.output.push_str(rest);
 	}
 }
 
 impl<T: StringLike> StringLike for Indented<T> {
 	fn push(&mut self, c: char) {
 		if c == '\n' {
+			if self.escape {
+				self.output.push(' ');
+				return;
+			}
 			self.output.reserve(self.indent + 1);
 			self.output.push('\n');
 			for _ in 0..self.indent {
@@ -84,6 +255,10 @@ impl<T: StringLike> StringLike for Indented<T> {
 	}
 
 	fn push_str(&mut self, s: &str) {
+		if self.escape {
+			self.push_escaped(s);
+			return;
+		}
 		let mut lines = s.split('\n');
 		if let Some(first_line) = lines.next() {
 			self.output.push_str(first_line);
@@ -106,7 +281,8 @@ impl<T: StringLike> StringLike for Indented<T> {
 
 impl<T: StringLike> fmt::Write for Indented<T> {
 	fn write_char(&mut self, c: char) -> std::fmt::Result {
-		self.push(c);
+		// formatted text is always content, even a lone newline
+		self.push_str(c.encode_utf8(&mut [0; 4]));
 		Ok(())
 	}
 
@@ -115,3 +291,30 @@ impl<T: StringLike> fmt::Write for Indented<T> {
 		Ok(())
 	}
 }
+
+/// Write a duration in a compact form, like `12.3ms` or `1.5s`.
+pub(super) fn push_duration<S: StringLike>(output: &mut S, duration: Duration) {
+	let nanos = duration.as_nanos();
+	if nanos == 0 {
+		output.push('0');
+		return;
+	}
+	if nanos < 1_000 {
+		output.push_str(itoa::Buffer::new().format(nanos));
+		output.push_str("ns");
+		return;
+	}
+
+	// in tenths of the unit
+	let (tenths, unit) = if nanos < 1_000_000 {
+		(nanos / 100, "µs")
+	} else if nanos < 1_000_000_000 {
+		(nanos / 100_000, "ms")
+	} else {
+		(nanos / 100_000_000, "s")
+	};
+	output.push_str(itoa::Buffer::new().format(tenths / 10));
+	output.push('.');
+	output.push_str(itoa::Buffer::new().format(tenths % 10));
+	output.push_str(unit);
+}